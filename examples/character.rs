@@ -1,11 +1,34 @@
 // This example creates a simple bevy app that loads a character animation spritesheet and allows
 // the user to switch animations by using the number keys 0-9 and the top row letter keys Q-P.
 // The spritesheet animations are set up to use some useful features such as animation transitions,
-// animation end actions, and animation events.
+// animation end actions, and animation events. The end actions are declared in the sidecar file
+// 'assets/witch.sprite.anim.ron', which is applied when the spritesheet is loaded.
 
 use bevy::{prelude::*, render::camera::ClearColorConfig, sprite::Anchor};
 use bevy_asepritesheet::prelude::*;
 
+// Animation Labels: ----------------------------------------------------------
+
+anim_labels! {
+    /// Every animation in 'assets/witch.sprite.json', verified against the frame tags when the
+    /// spritesheet is loaded
+    enum WitchAnim {
+        Idle = "idle",
+        Run = "run",
+        Bow = "bow",
+        JumpPrepare = "jump_prepare",
+        Jump = "jump",
+        FallTransition = "fall_transition",
+        Falling = "falling",
+        FallLand = "fall_land",
+        Slide = "slide",
+        AttackLight = "attack_light",
+        AttackHeavy = "attack_heavy",
+        Hurt = "hurt",
+        FaceBackground = "face_background",
+    }
+}
+
 // Entry Point: ----------------------------------------------------------------
 
 fn main() {
@@ -17,6 +40,9 @@ fn main() {
             // an animated spritesheet is complete
             AsepritesheetPlugin::new(&["sprite.json"]).in_schedule(Update),
         ))
+        // a warning listing the missing and extra animations is logged whenever the spritesheet
+        // is loaded if the labels don't match it
+        .verify_anim_labels::<WitchAnim>("witch.sprite.json")
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -56,8 +82,10 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         // here we can define a callback with a mutable reference to the spritesheet that was
         // loaded so that it can be tweaked after loading is complete
         |sheet| {
-            println!("Spritesheet finished loading!");
-            format_witch_anims(sheet);
+            println!(
+                "Spritesheet finished loading! 'run' is an alias for animation {:?}",
+                sheet.get_label_handle(WitchAnim::Run)
+            );
         },
     );
 
@@ -111,7 +139,6 @@ fn control_animation(input: Res<ButtonInput<KeyCode>>, mut query: Query<&mut Spr
 
     // apply the animation index, or log warning if invalid index
     for mut sprite_animator in &mut query {
-        sprite_animator.time_scale = 1.0;
         sprite_animator.set_anim_index(anim_index);
     }
 }
@@ -155,68 +182,3 @@ fn log_load_events(
         )
     }
 }
-
-// Utility: --------------------------------------------------------------------
-
-/// format the spritesheet animations for the witch character
-fn format_witch_anims(sheet: &mut Spritesheet) {
-    // get handles for all the needed animations
-    let handle_idle = sheet.get_anim_handle("idle");
-    let _handle_running = sheet.get_anim_handle("running");
-    let handle_bow = sheet.get_anim_handle("bow");
-    let handle_jump_prepare = sheet.get_anim_handle("jump_prepare");
-    let handle_jump = sheet.get_anim_handle("jump");
-    let handle_fall_transition = sheet.get_anim_handle("fall_transition");
-    let handle_falling = sheet.get_anim_handle("falling");
-    let handle_fall_land = sheet.get_anim_handle("fall_land");
-    let _handle_slide = sheet.get_anim_handle("slide");
-    let handle_attack_light = sheet.get_anim_handle("attack_light");
-    let handle_attack_heavy = sheet.get_anim_handle("attack_heavy");
-    let handle_damage = sheet.get_anim_handle("damage");
-    let _handle_face_background = sheet.get_anim_handle("face_background");
-
-    // have the bow animation pause at the end
-    if let Ok(anim_bow) = sheet.get_anim_mut(&handle_bow) {
-        anim_bow.end_action = AnimEndAction::Pause;
-    }
-
-    // when the jump prepare animation finishes, play the jump animation
-    if let Ok(anim_jump_prepare) = sheet.get_anim_mut(&handle_jump_prepare) {
-        anim_jump_prepare.end_action = AnimEndAction::Next(handle_jump);
-    }
-
-    // when the jump animation finishes, play the fall transition animation
-    if let Ok(anim_jump) = sheet.get_anim_mut(&handle_jump) {
-        anim_jump.end_action = AnimEndAction::Next(handle_fall_transition);
-    }
-
-    // when the fall transition animation finishes, play the falling animation
-    if let Ok(anim_fall_transition) = sheet.get_anim_mut(&handle_fall_transition) {
-        anim_fall_transition.end_action = AnimEndAction::Next(handle_falling);
-    }
-
-    // when the falling animation finishes, play the fall land animation
-    if let Ok(anim_falling) = sheet.get_anim_mut(&handle_falling) {
-        anim_falling.end_action = AnimEndAction::Next(handle_fall_land);
-    }
-
-    // when the fall land animation finishes, play the idle animation
-    if let Ok(anim_fall_land) = sheet.get_anim_mut(&handle_fall_land) {
-        anim_fall_land.end_action = AnimEndAction::Next(handle_idle);
-    }
-
-    // when the attack light animation finishes, play the idle animation
-    if let Ok(anim_attack_light) = sheet.get_anim_mut(&handle_attack_light) {
-        anim_attack_light.end_action = AnimEndAction::Next(handle_idle);
-    }
-
-    // when the attack_heavy animation finishes, play the idle animation
-    if let Ok(anim_attack_heavy) = sheet.get_anim_mut(&handle_attack_heavy) {
-        anim_attack_heavy.end_action = AnimEndAction::Next(handle_idle);
-    }
-
-    // when the damage animation finishes, play the idle animation
-    if let Ok(anim_damage) = sheet.get_anim_mut(&handle_damage) {
-        anim_damage.end_action = AnimEndAction::Next(handle_idle);
-    }
-}
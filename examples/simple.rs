@@ -1,6 +1,6 @@
 // This is the most basic use example from the readme.md
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::prelude::*;
 use bevy_asepritesheet::prelude::*;
 
 fn main() {
@@ -18,16 +18,11 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn(Camera2dBundle::default());
 
     // load the spritesheet and get it's handle
-    let sheet_handle = load_spritesheet(
-        &mut commands,
-        &asset_server,
-        "witch.sprite.json",
-        Anchor::Center,
-    );
+    let sheet_handle: Handle<Spritesheet> = asset_server.load("witch.sprite.json");
 
     // spawn the animated sprite
     commands.spawn(AnimatedSpriteBundle {
-        animator: SpriteAnimator::from_anim(AnimHandle::from_index(1)),
+        animator: SpriteAnimator::from_anim_name("running"),
         spritesheet: sheet_handle,
         ..Default::default()
     });
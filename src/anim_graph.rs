@@ -0,0 +1,340 @@
+use crate::{
+    animator::SpriteAnimator,
+    sprite::*,
+    state_machine::{AnimCondition, AnimParam, AnimStateMachine, AnimTransition},
+};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::Deserialize;
+
+// Struct Definitions: ---------------------------------------------------------
+
+/// A data driven description of how a character's animations behave and
+/// transition between each other, loaded from `.animgraph.ron` files. Add a
+/// [`Handle<AnimGraph>`] to an animated sprite entity and an
+/// [`AnimStateMachine`] is built for it from the graph, which is rebuilt
+/// whenever the graph or the spritesheet changes so that the graph can be
+/// tuned and hot reloaded without recompiling
+///
+/// # Example
+/// ```ron
+/// (
+///     spritesheet: "witch.sprite.json",
+///     initial: "idle",
+///     params: { "speed": Float(0.0) },
+///     states: {
+///         "attack_light": (end_action: Next("idle"), time_scale: 1.5),
+///     },
+///     transitions: [
+///         (from: "idle", to: "running", conditions: [FloatGreater("speed", 0.1)]),
+///         (to: "damage", conditions: [Trigger("hurt")]),
+///     ],
+/// )
+/// ```
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default)]
+pub struct AnimGraph {
+    /// The asset path of the spritesheet that the graph was authored for
+    #[serde(default)]
+    pub spritesheet: Option<String>,
+    /// The spritesheet at [`AnimGraph::spritesheet`], which is loaded along
+    /// with the graph. Entities that have a graph but no spritesheet are given
+    /// this spritesheet
+    #[serde(skip)]
+    #[dependency]
+    pub spritesheet_handle: Option<Handle<Spritesheet>>,
+    /// The name of the animation that is played if the animator isn't
+    /// playing one when the graph is applied
+    #[serde(default)]
+    pub initial: Option<String>,
+    /// The initial values of the state machine parameters, values that were
+    /// already set on the state machine are kept when the graph is applied
+    #[serde(default)]
+    pub params: HashMap<String, AnimParam>,
+    /// Settings for each animation, by animation name, which the animators of
+    /// the entities using the graph play the animations with instead of the
+    /// spritesheet's settings
+    #[serde(default)]
+    pub states: HashMap<String, AnimSettings>,
+    /// The transitions between animations, in the order they're checked
+    #[serde(default)]
+    pub transitions: Vec<AnimGraphTransition>,
+}
+
+/// An [`AnimTransition`] that refers to animations by name
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct AnimGraphTransition {
+    /// The name of the animation that the transition is taken from, or
+    /// [`None`] if it can be taken from any animation
+    #[serde(default)]
+    pub from: Option<String>,
+    /// The name of the animation that the transition plays
+    pub to: String,
+    /// See [`AnimTransition::conditions`]
+    #[serde(default)]
+    pub conditions: Vec<AnimCondition>,
+    /// See [`AnimTransition::exit_time`]
+    #[serde(default)]
+    pub exit_time: Option<f32>,
+    /// See [`AnimTransition::wait_for_end`]
+    #[serde(default)]
+    pub wait_for_end: bool,
+}
+
+// Struct Implementations: -----------------------------------------------------
+
+impl AnimGraph {
+    /// Override the end actions and time scales of the specified animator
+    /// with the graph's states, resolved on the specified spritesheet. Only
+    /// the animator is affected, the spritesheet and the other animators that
+    /// use it are left as they are
+    pub fn apply_to_animator(&self, animator: &mut SpriteAnimator, sheet: &Spritesheet) {
+        animator.clear_anim_overrides();
+        for (name, settings) in &self.states {
+            let handle = sheet.get_anim_handle(name);
+            let Ok(anim) = sheet.get_anim(&handle) else {
+                warn!("Animation graph state '{}' is not in the spritesheet", name);
+                continue;
+            };
+            let (end_action, time_scale) = settings.resolve(sheet, anim);
+            animator.set_anim_override(handle, end_action, time_scale);
+        }
+    }
+
+    /// Replace the transitions of the specified state machine with the
+    /// graph's transitions, resolved on the specified spritesheet, and add
+    /// any parameters it doesn't have yet
+    pub fn apply_to_state_machine(
+        &self,
+        state_machine: &mut AnimStateMachine,
+        sheet: &Spritesheet,
+    ) {
+        state_machine.clear_transitions();
+        for transition in &self.transitions {
+            if let Some(transition) = transition.resolve(sheet) {
+                state_machine.add_transition(transition);
+            }
+        }
+        for (name, value) in &self.params {
+            if state_machine.get_param(name).is_none() {
+                state_machine.set_param(name.clone(), *value);
+            }
+        }
+    }
+
+    /// Create a new state machine from the graph, resolved on the specified
+    /// spritesheet
+    pub fn build_state_machine(&self, sheet: &Spritesheet) -> AnimStateMachine {
+        let mut state_machine = AnimStateMachine::new();
+        self.apply_to_state_machine(&mut state_machine, sheet);
+        state_machine
+    }
+}
+
+impl AnimGraphTransition {
+    /// Create the transition on the specified spritesheet, or [`None`] if it
+    /// refers to animations that aren't in the spritesheet
+    pub fn resolve(&self, sheet: &Spritesheet) -> Option<AnimTransition> {
+        let find = |name: &String| {
+            let handle = sheet.get_anim_handle(name);
+            if sheet.get_anim(&handle).is_err() {
+                warn!(
+                    "Animation graph transition refers to missing animation '{}'",
+                    name
+                );
+                return None;
+            }
+            Some(handle)
+        };
+        let to = find(&self.to)?;
+        let from = match &self.from {
+            Some(name) => Some(find(name)?),
+            None => None,
+        };
+        Some(AnimTransition {
+            from,
+            to,
+            conditions: self.conditions.clone(),
+            exit_time: self.exit_time,
+            wait_for_end: self.wait_for_end,
+        })
+    }
+}
+
+// Systems: --------------------------------------------------------------------
+
+/// system that applies each entity's [`AnimGraph`] to its [`SpriteAnimator`]
+/// and [`AnimStateMachine`] when the graph handle is added or changed, or when
+/// the graph or spritesheet assets are loaded or modified
+#[allow(clippy::type_complexity)]
+pub fn sync_anim_graphs(
+    mut commands: Commands,
+    mut graph_events: EventReader<AssetEvent<AnimGraph>>,
+    mut sheet_events: EventReader<AssetEvent<Spritesheet>>,
+    graph_assets: Res<Assets<AnimGraph>>,
+    spritesheet_assets: Res<Assets<Spritesheet>>,
+    mut query: Query<(
+        Entity,
+        Ref<Handle<AnimGraph>>,
+        Option<Ref<Handle<Spritesheet>>>,
+        Option<&mut AnimStateMachine>,
+        Option<&mut SpriteAnimator>,
+    )>,
+) {
+    // find the assets which have changed since the last update
+    let changed_graphs: HashSet<AssetId<AnimGraph>> = graph_events
+        .read()
+        .filter_map(|evt| match evt {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let changed_sheets: HashSet<AssetId<Spritesheet>> = sheet_events
+        .read()
+        .filter_map(|evt| match evt {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (ent, graph_handle, sheet_handle, state_machine, animator) in &mut query {
+        if !graph_handle.is_changed()
+            && !changed_graphs.contains(&graph_handle.id())
+            && !sheet_handle
+                .as_ref()
+                .is_some_and(|handle| handle.is_changed() || changed_sheets.contains(&handle.id()))
+        {
+            continue;
+        }
+        let Some(graph) = graph_assets.get(graph_handle.as_ref()) else {
+            continue;
+        };
+
+        // use the graph's spritesheet if the entity doesn't have one
+        let sheet_handle = match (sheet_handle.as_deref(), &graph.spritesheet_handle) {
+            (Some(handle), _) => handle,
+            (None, Some(handle)) => {
+                commands.entity(ent).insert((
+                    handle.clone(),
+                    NeedsSheetImageHandle,
+                    NeedsSheetAtlasHandle,
+                ));
+                handle
+            }
+            (None, None) => continue,
+        };
+
+        let Some(sheet) = spritesheet_assets.get(sheet_handle) else {
+            continue;
+        };
+        if sheet.anim_count() == 0 {
+            continue;
+        }
+
+        match state_machine {
+            Some(mut state_machine) => graph.apply_to_state_machine(&mut state_machine, sheet),
+            None => {
+                commands
+                    .entity(ent)
+                    .insert(graph.build_state_machine(sheet));
+            }
+        }
+        if let Some(mut animator) = animator {
+            graph.apply_to_animator(&mut animator, sheet);
+            if let Some(initial) = &graph.initial {
+                if animator.cur_anim().is_none() && animator.pending_anim_name().is_none() {
+                    animator.set_anim(sheet.get_anim_handle(initial));
+                }
+            }
+        }
+    }
+}
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{animator::tests::test_sheet, assets::ron_options};
+
+    #[test]
+    fn graph_loads_from_ron() {
+        let graph: AnimGraph = ron_options()
+            .from_bytes(include_bytes!("../assets/witch.animgraph.ron"))
+            .unwrap();
+        assert_eq!(graph.spritesheet.as_deref(), Some("witch.sprite.json"));
+        assert_eq!(graph.initial.as_deref(), Some("idle"));
+        assert_eq!(graph.params.get("speed"), Some(&AnimParam::Float(0.0)));
+        assert_eq!(
+            graph.states.get("jump"),
+            Some(&AnimSettings {
+                end_action: Some(AnimEndActionDef::Next("fall_transition".to_string())),
+                time_scale: None,
+            })
+        );
+        assert_eq!(graph.transitions.len(), 7);
+        assert_eq!(graph.transitions[5].exit_time, Some(0.5));
+        assert_eq!(graph.transitions[6].from, None);
+        assert_eq!(graph.transitions[6].to, "damage");
+    }
+
+    #[test]
+    fn sync_overrides_animator_settings() {
+        let sheet = test_sheet(&[("attack", 0, 1, "forward"), ("idle", 2, 3, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let idle = sheet.get_anim_handle("idle");
+        let graph: AnimGraph = ron_options()
+            .from_str(
+                r#"(
+                    initial: "idle",
+                    states: { "attack": (end_action: Next("idle"), time_scale: 2.0) },
+                    transitions: [(from: "idle", to: "attack", conditions: [Trigger("attack")])],
+                )"#,
+            )
+            .unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Spritesheet>()
+            .init_asset::<AnimGraph>()
+            .add_systems(Update, sync_anim_graphs);
+        let sheet_handle = app.world.resource_mut::<Assets<Spritesheet>>().add(sheet);
+        let graph_handle = app.world.resource_mut::<Assets<AnimGraph>>().add(graph);
+        let graph_ent = app
+            .world
+            .spawn((
+                graph_handle,
+                sheet_handle.clone(),
+                SpriteAnimator::default(),
+            ))
+            .id();
+        let plain_ent = app
+            .world
+            .spawn((sheet_handle.clone(), SpriteAnimator::default()))
+            .id();
+        app.update();
+
+        // the states are overrides on the animator, not on the spritesheet
+        let sheet = app
+            .world
+            .resource::<Assets<Spritesheet>>()
+            .get(&sheet_handle)
+            .unwrap();
+        let animator = app.world.get::<SpriteAnimator>(graph_ent).unwrap();
+        assert_eq!(
+            animator.anim_settings(&attack, sheet),
+            Some((AnimEndAction::Next(idle), 2.0))
+        );
+        assert!(animator.is_cur_anim(idle));
+        let plain = app.world.get::<SpriteAnimator>(plain_ent).unwrap();
+        assert_eq!(
+            plain.anim_settings(&attack, sheet),
+            Some((AnimEndAction::Loop, 1.0))
+        );
+        let state_machine = app.world.get::<AnimStateMachine>(graph_ent).unwrap();
+        assert_eq!(state_machine.transitions().len(), 1);
+    }
+}
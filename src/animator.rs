@@ -1,5 +1,6 @@
 use crate::{core::SpriteAnimController, sprite::*};
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{ecs::system::SystemParam, prelude::*, sprite::Anchor, utils::HashMap};
+use std::{collections::VecDeque, ops::RangeInclusive};
 
 // Struct Definitions: ---------------------------------------------------------
 
@@ -12,6 +13,67 @@ pub enum AnimTimestamp {
     /// a value of 0 will always be the beginning and a value of 1 will always be the very end of
     /// the animation, no matter how long or short it is
     Normalized(f32),
+    /// the start of the frame at the specified index within the animation, the last frame is used
+    /// if the index is past the end of the animation
+    Frame(usize),
+}
+
+/// Specifies where the new animation starts playing when the animation is
+/// changed with [`SpriteAnimator::set_anim_with`]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionMode {
+    /// Play the new animation from the beginning, even if it's the current
+    /// animation
+    #[default]
+    Restart,
+    /// Continue at the same point in the cycle, for example halfway through
+    /// the new animation if the old one was halfway through
+    KeepNormalizedTime,
+    /// Continue at the same amount of seconds since the animation started
+    KeepAbsoluteTime,
+    /// Continue on the frame with the same index within the animation, or the
+    /// last frame if the new animation is shorter
+    KeepFrameIndex,
+}
+
+/// What happens when playback reaches the end of a frame range set with
+/// [`SpriteAnimator::play_range`]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeMode {
+    /// Continue from the first frame of the range
+    #[default]
+    Loop,
+    /// Keep showing the last frame of the range
+    Hold,
+}
+
+/// An animation change made with [`SpriteAnimator::set_anim_with`] that is
+/// applied when the animator is next animated, since the spritesheet is needed
+#[derive(Clone, Copy)]
+struct PendingTransition {
+    mode: TransitionMode,
+    from: AnimHandle,
+    time: f32,
+    frame: usize,
+}
+
+/// The animation that the animator last played, remembered so that its handle
+/// can be remapped if the spritesheet changes
+#[derive(Clone)]
+struct ResolvedAnim {
+    handle: AnimHandle,
+    name: String,
+    frame_count: usize,
+    total_time: f32,
+}
+
+/// An animation that the animator keeps a handle to, such as a queued one,
+/// along with the name of the animation once it's known, so that the handle
+/// can be remapped if the spritesheet changes
+#[derive(Clone)]
+struct KeptAnim {
+    handle: AnimHandle,
+    name: Option<String>,
 }
 
 /// A component used to animate a [`TextureAtlasSprite`], which contains a
@@ -23,7 +85,22 @@ pub struct SpriteAnimator {
     cur_anim: Option<AnimHandle>,
     last_frame_start: f32,
     last_anim_index: usize,
+    entered_anim_index: Option<usize>,
+    loop_count: u32,
     target_time: Option<AnimTimestamp>,
+    pending_anim: Option<String>,
+    resolved_anim: Option<ResolvedAnim>,
+    queue: VecDeque<(KeptAnim, u32)>,
+    queued_repeats: u32,
+    prev_anim: Option<KeptAnim>,
+    ended_anim: Option<KeptAnim>,
+    paused: bool,
+    finished: bool,
+    transition: Option<PendingTransition>,
+    range: Option<(RangeInclusive<usize>, RangeMode)>,
+    /// the end actions and time scales that animations are played with
+    /// instead of the spritesheet's, by animation index
+    anim_overrides: HashMap<usize, (AnimEndAction, f32)>,
 }
 
 #[derive(Bundle, Default, Clone)]
@@ -35,15 +112,98 @@ pub struct AnimatedSpriteBundle {
     pub needs_atlas: NeedsSheetAtlasHandle,
 }
 
+/// Marks an entity so that its [`SpriteAnimator`] sends animation events, such
+/// as [`AnimFinishEvent`], when it's animated by [`animate_sprites`]
 #[derive(Component, Clone, Copy)]
 pub struct AnimEventSender;
 
+/// An opt-in component which holds the rects of every spritesheet [`Slice`]
+/// that is active on the frame currently shown by the [`SpriteAnimator`] on
+/// the same entity. It's kept up to date by [`animate_sprites`], and mirrored
+/// to match [`Sprite::flip_x`] and [`Sprite::flip_y`]
+#[derive(Component, Default, Clone, Debug)]
+pub struct SliceColliders {
+    colliders: Vec<SliceCollider>,
+    /// the id of the spritesheet that the colliders were taken from, or
+    /// [`None`] if they need to be updated even if the frame is the same
+    sheet: Option<u64>,
+    frame: Option<usize>,
+    flip: (bool, bool),
+}
+
+/// The shape of a [`Slice`] on the current frame, relative to the entity's
+/// transform
+#[derive(Clone, Debug, PartialEq)]
+pub struct SliceCollider {
+    /// The name of the slice as defined in aseprite
+    pub name: String,
+    /// The bounds of the slice
+    pub rect: Rect,
+    /// The pivot point of the slice, if it has one
+    pub pivot: Option<Vec2>,
+}
+
 #[derive(Event, Debug, Clone)]
 pub struct AnimFinishEvent {
     pub entity: Entity,
     pub anim: AnimHandle,
 }
 
+/// Event which is sent when an animation enters a frame that has
+/// [`UserData`] attached to it in aseprite, either on one of the frame's cels
+/// or, for the first frame of an animation, on the animation's frame tag. One
+/// event is sent for each piece of user data, on every frame that is entered,
+/// even if multiple frames are passed in a single update
+#[derive(Event, Debug, Clone)]
+pub struct AnimFrameEvent {
+    pub entity: Entity,
+    pub anim: AnimHandle,
+    /// The index of the frame within the animation that was entered
+    pub frame: usize,
+    pub payload: UserData,
+}
+
+/// Event which is sent when an animation starts playing from the beginning,
+/// either because it was just set on the animator or because it was restarted
+#[derive(Event, Debug, Clone)]
+pub struct AnimStartedEvent {
+    pub entity: Entity,
+    pub anim: AnimHandle,
+}
+
+/// Event which is sent each time a looping animation wraps back around to its
+/// first frame
+#[derive(Event, Debug, Clone)]
+pub struct AnimLoopedEvent {
+    pub entity: Entity,
+    pub anim: AnimHandle,
+    /// How many times the animation has looped since it started
+    pub loop_count: u32,
+}
+
+/// Event which is sent each time the animation moves from one frame to
+/// another, not including when an animation starts
+#[derive(Event, Debug, Clone)]
+pub struct AnimFrameChangedEvent {
+    pub entity: Entity,
+    pub anim: AnimHandle,
+    /// The index of the frame within the animation that was being shown
+    pub old_frame: usize,
+    /// The index of the frame within the animation that is now being shown
+    pub new_frame: usize,
+}
+
+/// All the event writers that a [`SpriteAnimator`] can send animation events
+/// through
+#[derive(SystemParam)]
+pub struct AnimEventWriters<'w> {
+    pub finish: EventWriter<'w, AnimFinishEvent>,
+    pub frame: EventWriter<'w, AnimFrameEvent>,
+    pub started: EventWriter<'w, AnimStartedEvent>,
+    pub looped: EventWriter<'w, AnimLoopedEvent>,
+    pub frame_changed: EventWriter<'w, AnimFrameChangedEvent>,
+}
+
 /// the system set where the sprites are animated, but it really only contains one system
 /// [`animate_sprites`]
 #[derive(SystemSet, Debug, Reflect, Clone, Copy, Hash, PartialEq, Eq)]
@@ -59,7 +219,20 @@ impl Default for SpriteAnimator {
             cur_anim: default(),
             last_frame_start: default(),
             last_anim_index: default(),
+            entered_anim_index: default(),
+            loop_count: default(),
             target_time: default(),
+            pending_anim: default(),
+            resolved_anim: default(),
+            queue: default(),
+            queued_repeats: default(),
+            prev_anim: default(),
+            ended_anim: default(),
+            paused: default(),
+            finished: default(),
+            transition: default(),
+            range: default(),
+            anim_overrides: default(),
         }
     }
 }
@@ -70,11 +243,7 @@ impl SpriteAnimator {
     pub fn new(time_scale: f32) -> Self {
         Self {
             time_scale,
-            cur_time: default(),
-            cur_anim: default(),
-            last_frame_start: default(),
-            last_anim_index: default(),
-            target_time: default(),
+            ..default()
         }
     }
 
@@ -86,11 +255,95 @@ impl SpriteAnimator {
         }
     }
 
+    /// Create a sprite animator that plays the animation with the specified
+    /// name or alias once the spritesheet has loaded, see
+    /// [`SpriteAnimator::set_anim_name`]
+    pub fn from_anim_name(name: impl Into<String>) -> Self {
+        Self {
+            pending_anim: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
     /// The current animation playtime elapsed since the animation was started
     pub fn cur_time(&self) -> f32 {
         self.cur_time
     }
 
+    /// The index of the frame being shown within the current animation, if an
+    /// animation is playing, which can be used with [`AnimTimestamp::Frame`]
+    pub fn cur_frame_index(&self) -> Option<usize> {
+        self.cur_anim.map(|_| self.last_anim_index)
+    }
+
+    /// The index of the frame being shown in the texture atlas of the
+    /// spritesheet, if an animation is playing
+    pub fn cur_atlas_index(&self, sheet: &Spritesheet) -> Option<usize> {
+        self.cur_sheet_frame(sheet)
+            .map(|frame_index| sheet.frames[frame_index].atlas_index)
+    }
+
+    /// How long the current frame has been shown for, in seconds of
+    /// animation time, which isn't affected by the time scales
+    pub fn time_in_frame(&self) -> f32 {
+        self.cur_time - self.last_frame_start
+    }
+
+    /// How long it takes the current animation to reach its end from the
+    /// current time, or the end of the current loop for looping animations,
+    /// in seconds of animation time, which isn't affected by the time scales
+    pub fn time_remaining(&self, sheet: &Spritesheet) -> f32 {
+        let Some(cur_anim) = self
+            .cur_anim
+            .and_then(|handle| sheet.get_anim(&handle).ok())
+        else {
+            return 0.0;
+        };
+        let total_time = cur_anim.total_time();
+        let loop_time = self.cur_time - self.loop_count as f32 * total_time;
+        (total_time - loop_time).max(0.0)
+    }
+
+    /// How many times the current animation has looped since it was started
+    pub fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+
+    /// Play the specified animation with a different end action and time
+    /// scale than the ones it has in the spritesheet, without affecting the
+    /// other animators that use the spritesheet. The animation is referred to
+    /// by its index, so the override should be set again if the animator is
+    /// given a different spritesheet, which [`crate::anim_graph::AnimGraph`]
+    /// does automatically
+    pub fn set_anim_override(
+        &mut self,
+        anim: AnimHandle,
+        end_action: AnimEndAction,
+        time_scale: f32,
+    ) {
+        if let Some(index) = anim.index() {
+            self.anim_overrides.insert(index, (end_action, time_scale));
+        }
+    }
+
+    /// Play every animation with the end action and time scale that it has in
+    /// the spritesheet
+    pub fn clear_anim_overrides(&mut self) {
+        self.anim_overrides.clear();
+    }
+
+    /// The end action and time scale that the animator plays the specified
+    /// animation with, which are the ones it has in the spritesheet unless
+    /// they're overridden with [`SpriteAnimator::set_anim_override`]
+    pub fn anim_settings(
+        &self,
+        anim: &AnimHandle,
+        sheet: &Spritesheet,
+    ) -> Option<(AnimEndAction, f32)> {
+        let anim_data = sheet.get_anim(anim).ok()?;
+        Some(self.resolve_settings(anim, anim_data))
+    }
+
     /// The elapsed time in the current animation normalized from 0 to 1, 0
     /// meaning none of the animation has played, while 1 means the entire
     /// animation has played
@@ -112,38 +365,61 @@ impl SpriteAnimator {
     /// Set the current elapsed time of the currently playing animation
     pub fn set_cur_time(&mut self, time: AnimTimestamp) {
         self.target_time = Some(time);
+        self.finished = false;
     }
 
     /// Set the current elapsed time in the animation
     fn set_cur_time_seconds(&mut self, seconds: f32, sheet: &Spritesheet) {
         // return if no anim
-        let cur_anim = sheet.get_anim(self.cur_anim.as_ref().unwrap()).unwrap();
+        let Some(cur_anim) = self
+            .cur_anim
+            .and_then(|handle| sheet.get_anim(&handle).ok())
+        else {
+            return;
+        };
 
-        // some optomization in case the user inputs a high value, to avoid
-        // looping through the animation multiple times in the next
-        // animation call
-        let mut target_time = seconds;
-        if self.cur_time > seconds {
-            if cur_anim.end_action == AnimEndAction::Loop {
-                let anim_time = cur_anim.total_time();
-                let loop_cur_time = self.cur_time % anim_time;
-                let loop_target_time = seconds % anim_time;
-                let target_loops = (seconds / anim_time).floor();
-                if loop_cur_time <= loop_target_time {
-                    self.last_frame_start = target_loops * anim_time + loop_cur_time;
-                } else {
-                    if target_loops > 0.0 {
-                        self.last_frame_start = target_loops * anim_time;
-                        self.last_anim_index = 0;
-                    } else {
-                        self.reset_persistent_data();
-                    }
-                }
-            } else {
-                target_time = cur_anim.total_time();
-                self.last_anim_index = sheet.anim_count() - 1;
-                self.last_frame_start = target_time - sheet.frames[self.last_anim_index].duration;
+        // looping animations can be seeked past the end, into any loop, while
+        // animations that loop a limited amount of times are clamped to the
+        // end of their last loop, and other animations to their length
+        let (end_action, _) = self.resolve_settings(&self.cur_anim.unwrap(), cur_anim);
+        let anim_time = cur_anim.total_time();
+        let mut target_time = seconds.max(0.0);
+        let max_loops = match end_action {
+            _ if anim_time <= 0.0 => Some(1),
+            AnimEndAction::Loop => None,
+            AnimEndAction::LoopCount(count) => Some(count.max(1)),
+            _ => Some(1),
+        };
+        if let Some(max_loops) = max_loops {
+            target_time = target_time.min(max_loops as f32 * anim_time);
+        }
+        let mut loops = if anim_time > 0.0 {
+            (target_time / anim_time).floor() as u32
+        } else {
+            0
+        };
+        if let Some(max_loops) = max_loops {
+            // the very end of the last loop is still part of it
+            loops = loops.min(max_loops - 1);
+        }
+        self.loop_count = loops;
+        let loop_start = loops as f32 * anim_time;
+
+        // find the frame that the target time lands on within the loop
+        self.last_anim_index = 0;
+        self.last_frame_start = loop_start;
+        let anim_len = cur_anim.frame_indices().len();
+        for frame_index in cur_anim
+            .frame_indices()
+            .iter()
+            .take(anim_len.saturating_sub(1))
+        {
+            let duration = sheet.frames[*frame_index].duration;
+            if self.last_frame_start + duration >= target_time {
+                break;
             }
+            self.last_frame_start += duration;
+            self.last_anim_index += 1;
         }
         self.cur_time = target_time;
     }
@@ -167,7 +443,7 @@ impl SpriteAnimator {
     /// Check to see if the current animation matches the specified animation
     pub fn is_cur_anim(&self, handle: AnimHandle) -> bool {
         if let Some(cur_anim) = self.cur_anim {
-            cur_anim == handle
+            cur_anim.matches(&handle)
         } else {
             false
         }
@@ -175,14 +451,89 @@ impl SpriteAnimator {
 
     /// Start playing the specified animation and returns true if it exists, else returns false
     pub fn set_anim(&mut self, anim: AnimHandle) {
+        if !self.is_cur_anim(anim) {
+            self.range = None;
+            self.finished = false;
+        }
+        self.pending_anim = None;
+        self.transition = None;
+        self.queued_repeats = 0;
+        self.ended_anim = None;
         if let Some(cur_anim) = self.cur_anim {
-            if cur_anim != anim {
+            if !cur_anim.matches(&anim) {
                 self.restart_anim();
+                let name = self
+                    .resolved_anim
+                    .as_ref()
+                    .filter(|resolved| resolved.handle == cur_anim)
+                    .map(|resolved| resolved.name.clone());
+                self.prev_anim = Some(KeptAnim {
+                    handle: cur_anim,
+                    name,
+                });
             }
         }
         self.cur_anim = Some(anim);
     }
 
+    /// Start playing the specified animation, with the mode deciding where in
+    /// the animation it starts from based on the current animation
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_asepritesheet::prelude::*;
+    /// # fn shoot(animator: &mut SpriteAnimator, sheet: &Spritesheet) {
+    /// // keep the legs in step while starting to shoot
+    /// let run_shoot = sheet.get_anim_handle("run_shoot");
+    /// animator.set_anim_with(run_shoot, TransitionMode::KeepNormalizedTime);
+    /// # }
+    /// ```
+    pub fn set_anim_with(&mut self, anim: AnimHandle, mode: TransitionMode) {
+        // changing the animation again before the transition was applied
+        // still continues from the original animation
+        let (from, time, frame) = match self.transition.take() {
+            Some(transition) => (Some(transition.from), transition.time, transition.frame),
+            None => (self.cur_anim, self.cur_time, self.last_anim_index),
+        };
+        self.set_anim(anim);
+        match (mode, from) {
+            (TransitionMode::Restart, _) | (_, None) => self.restart_anim(),
+            (mode, Some(from)) => {
+                self.transition = Some(PendingTransition {
+                    mode,
+                    from,
+                    time,
+                    frame,
+                });
+            }
+        }
+    }
+
+    /// Start playing the animation with the specified name or alias. The name
+    /// is resolved the next time the animator is updated, so it can be used
+    /// before the spritesheet has loaded, a warning is logged if the
+    /// spritesheet doesn't have the animation
+    pub fn set_anim_name(&mut self, name: impl Into<String>) {
+        self.pending_anim = Some(name.into());
+    }
+
+    /// The name of the animation set with [`SpriteAnimator::set_anim_name`]
+    /// that hasn't been resolved on the spritesheet yet, if there is one
+    pub fn pending_anim_name(&self) -> Option<&str> {
+        self.pending_anim.as_deref()
+    }
+
+    /// Switch to the specified animation without restarting, so that it plays
+    /// from the same time as the current animation. Used when the current
+    /// animation is moved to a different handle, such as when the spritesheet
+    /// is reloaded
+    pub(crate) fn retarget_anim(&mut self, anim: AnimHandle) {
+        self.cur_anim = Some(anim);
+        if self.target_time.is_none() {
+            self.target_time = Some(AnimTimestamp::Seconds(self.cur_time));
+        }
+    }
+
     /// Start playing the animation at the specified index
     pub fn set_anim_index(&mut self, anim_index: usize) {
         self.set_anim(AnimHandle::from_index(anim_index))
@@ -192,6 +543,94 @@ impl SpriteAnimator {
     pub fn stop_anim(&mut self) {
         self.reset_persistent_data();
         self.cur_anim = None;
+        self.prev_anim = None;
+        self.pending_anim = None;
+        self.ended_anim = None;
+        self.transition = None;
+        self.range = None;
+        self.queued_repeats = 0;
+        self.queue.clear();
+    }
+
+    /// Only play the frames at the specified indices within the current
+    /// animation, with the mode deciding what happens when playback reaches
+    /// the end of the range, until [`SpriteAnimator::release_range`] is
+    /// called or the animation is changed. Playback continues normally until
+    /// it reaches the range if it's before it, and is moved to the start of
+    /// the range if it's past it
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_asepritesheet::prelude::*;
+    /// # fn charge(animator: &mut SpriteAnimator, button_held: bool) {
+    /// if button_held {
+    ///     // loop the charging frames of the attack
+    ///     animator.play_range(3..=6, RangeMode::Loop);
+    /// } else {
+    ///     // finish the rest of the attack
+    ///     animator.release_range();
+    /// }
+    /// # }
+    /// ```
+    pub fn play_range(&mut self, frames: RangeInclusive<usize>, mode: RangeMode) {
+        if self.last_anim_index > *frames.end() {
+            self.target_time = Some(AnimTimestamp::Frame(*frames.start()));
+        }
+        self.range = Some((frames, mode));
+    }
+
+    /// Continue playing the rest of the animation normally from the current
+    /// frame after [`SpriteAnimator::play_range`]
+    pub fn release_range(&mut self) {
+        self.range = None;
+    }
+
+    /// The indices of the frames within the current animation that are being
+    /// played, if playback is limited to a range
+    pub fn cur_range(&self) -> Option<RangeInclusive<usize>> {
+        self.range.as_ref().map(|(frames, _)| frames.clone())
+    }
+
+    /// Play the specified animation once the current animation and the
+    /// animations queued before it have finished, instead of the end action of
+    /// the current animation. The queued animation is played on the next update
+    /// if no animation is playing, or if the current animation has already
+    /// finished, see [`SpriteAnimator::is_finished`]
+    pub fn queue_anim(&mut self, anim: AnimHandle) {
+        self.queue_anim_repeated(anim, 1);
+    }
+
+    /// Queue the specified animation to be played the specified amount of
+    /// times in a row, see [`SpriteAnimator::queue_anim`]. The end action of
+    /// the animation is only used if it's the last one in the queue
+    pub fn queue_anim_repeated(&mut self, anim: AnimHandle, count: u32) {
+        if count > 0 {
+            self.queue.push_back((KeptAnim::new(anim), count));
+        }
+    }
+
+    /// Remove all the queued animations, the current animation keeps playing
+    /// and uses its own end action
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+        self.queued_repeats = 0;
+    }
+
+    /// The amount of animations waiting in the queue
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Start playing the next animation in the queue from the beginning,
+    /// returns false if the queue is empty
+    fn play_next_queued(&mut self) -> bool {
+        let Some((anim, count)) = self.queue.pop_front() else {
+            return false;
+        };
+        self.set_anim(anim.handle);
+        self.restart_anim();
+        self.queued_repeats = count - 1;
+        true
     }
 
     /// Restart the curent animation from the beginning
@@ -199,10 +638,77 @@ impl SpriteAnimator {
         self.last_anim_index = 0;
         self.last_frame_start = 0.0;
         self.cur_time = 0.0;
+        self.entered_anim_index = None;
+        self.loop_count = 0;
+        self.finished = false;
+        self.ended_anim = None;
+    }
+
+    /// Pause the animator so that the current animation stops advancing, the
+    /// paused state is kept when the animation is changed and the time scale
+    /// is left untouched
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume advancing the animation after [`SpriteAnimator::pause`], or
+    /// play the animation again from the start if it was paused at its end by
+    /// [`AnimEndAction::Pause`]
+    pub fn resume(&mut self) {
+        self.paused = false;
+        if self.finished && self.cur_anim.is_some() {
+            self.restart_anim();
+        }
+    }
+
+    /// Whether the animator was paused with [`SpriteAnimator::pause`], or
+    /// its animation was paused at its end by [`AnimEndAction::Pause`]
+    pub fn is_paused(&self) -> bool {
+        self.paused || (self.finished && self.cur_anim.is_some())
+    }
+
+    /// Whether the last animation ended without continuing, because its end
+    /// action is [`AnimEndAction::Pause`], which keeps its last frame shown
+    /// until another animation is played or it's restarted, or because its
+    /// end action stopped it
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The animation that last reached its end and was stopped or replaced by
+    /// its end action, if there is one. It's kept until the animation is
+    /// changed, restarted or stopped
+    pub fn ended_anim(&self) -> Option<AnimHandle> {
+        self.ended_anim.as_ref().map(|ended| ended.handle)
+    }
+
+    /// Remember the names of the animations that the animator keeps handles
+    /// to for later, or remap the handles by name if the spritesheet changed,
+    /// such as when it was reloaded with its animations in a different order
+    /// or swapped for another spritesheet. Queued animations that aren't in
+    /// the spritesheet anymore are removed from the queue
+    pub(crate) fn resolve_kept_anims(&mut self, sheet: &Spritesheet) {
+        self.queue.retain_mut(|(anim, _)| {
+            let resolved = anim.resolve(sheet);
+            if !resolved {
+                warn!(
+                    "Queued animation '{}' is not in the spritesheet",
+                    anim.name.as_deref().unwrap_or_default()
+                );
+            }
+            resolved
+        });
+        for kept in [&mut self.prev_anim, &mut self.ended_anim] {
+            if kept.as_mut().is_some_and(|anim| !anim.resolve(sheet)) {
+                *kept = None;
+            }
+        }
     }
 
     /// Play and apply the animation to the specified [`TextureAtlasSprite`] over the specified
-    /// elapsed time (delta)
+    /// elapsed time (delta). Returns the end action of the animation if it stopped at its end, the
+    /// caller is responsible for [`AnimEndAction::Despawn`] and [`AnimEndAction::Hide`] since they
+    /// affect the entity, which [`animate_sprites`] does automatically
     pub fn animate(
         &mut self,
         delta: f32,
@@ -210,8 +716,59 @@ impl SpriteAnimator {
         sheet: &Spritesheet,
         sprite: &mut Sprite,
         atlas: &mut TextureAtlas,
-        maybe_evts: Option<&mut EventWriter<AnimFinishEvent>>,
-    ) {
+        mut maybe_evts: Option<&mut AnimEventWriters>,
+    ) -> Option<AnimEndAction> {
+        // resolve the animation that was set by name, once the spritesheet
+        // has its animations
+        if self.pending_anim.is_some() && sheet.anim_count() > 0 {
+            let name = self.pending_anim.take().unwrap();
+            let handle = sheet.get_anim_handle(&name);
+            if handle.is_valid() {
+                self.set_anim(handle);
+            } else {
+                warn!("Animation '{}' is not in the spritesheet", name);
+            }
+        }
+
+        // keep the queued and previous animations on the same animations if
+        // the spritesheet changed
+        if sheet.anim_count() > 0 {
+            self.resolve_kept_anims(sheet);
+        }
+
+        // start the queued animations if nothing is playing, or if the current
+        // animation has finished
+        if (self.cur_anim.is_none() || self.finished) && self.pending_anim.is_none() {
+            self.play_next_queued();
+        }
+
+        // handles from a different spritesheet, or to an animation that moved
+        // when the spritesheet was reloaded, are remapped to the animation
+        // with the same name if the animator has played them
+        if let Some(handle) = self
+            .cur_anim
+            .filter(|handle| !self.is_resolved_on(handle, sheet))
+        {
+            // wait for the spritesheet to be loaded
+            if sheet.anim_count() == 0 {
+                return None;
+            }
+            let remapped = self
+                .resolved_anim
+                .as_ref()
+                .filter(|resolved| resolved.handle == handle)
+                .map(|resolved| sheet.get_anim_handle(&resolved.name))
+                .filter(|remapped| sheet.get_anim(remapped).is_ok());
+            match remapped {
+                Some(remapped) => self.retarget_anim(remapped),
+                None => {
+                    warn!("Animation handle doesn't refer to an animation on the spritesheet!");
+                    self.stop_anim();
+                    return None;
+                }
+            }
+        }
+
         // return if no animation is playing
         let cur_anim = if let Some(val) = self.cur_anim.as_ref() {
             if let Ok(val2) = sheet.get_anim(val) {
@@ -219,17 +776,34 @@ impl SpriteAnimator {
             } else {
                 warn!("Invalid animation for spritesheet!");
                 self.cur_anim = None;
-                return;
+                return None;
             }
         } else {
-            return;
+            return None;
         };
 
+        // remember the name of the animation so it can be remapped
+        let handle = self.cur_anim.unwrap();
+        if !self
+            .resolved_anim
+            .as_ref()
+            .is_some_and(|resolved| resolved.is_for(&handle, cur_anim))
+        {
+            self.resolved_anim = Some(ResolvedAnim::new(handle, cur_anim));
+        }
+        let (anim_end_action, anim_time_scale) =
+            self.resolve_settings(&self.cur_anim.unwrap(), cur_anim);
+
         let frames = &sheet.frames;
         let anim_frame_indices = cur_anim.frame_indices();
+        if anim_frame_indices.is_empty() {
+            return None;
+        }
 
-        let mut cur_frame = &frames[anim_frame_indices[self.last_anim_index]];
-        let mut next_frame_time = self.last_frame_start + cur_frame.duration;
+        // seek to the point in the animation that the transition continues from
+        if let Some(transition) = self.transition.take() {
+            self.apply_transition(transition, sheet);
+        }
 
         if let Some(target_time) = self.target_time.take() {
             match target_time {
@@ -239,30 +813,125 @@ impl SpriteAnimator {
                 AnimTimestamp::Normalized(val) => {
                     self.set_cur_time_normalized(val, sheet);
                 }
+                AnimTimestamp::Frame(frame) => {
+                    self.set_cur_frame(frame, sheet);
+                }
             }
-        } else {
-            self.cur_time += delta * cur_anim.time_scale * self.time_scale;
+        } else if !self.paused && !self.finished {
+            self.cur_time += delta * anim_time_scale * self.time_scale;
+        }
+
+        // the animation may have become shorter than the frame being shown,
+        // such as when the spritesheet was changed
+        if self.last_anim_index >= anim_frame_indices.len() {
+            self.set_cur_time_seconds(self.cur_time, sheet);
+        }
+
+        let mut cur_frame = &frames[anim_frame_indices[self.last_anim_index]];
+        let mut next_frame_time = self.last_frame_start + cur_frame.duration;
+
+        // the current frame may not have been entered yet if the animation
+        // just started or if the animator was seeked to a different frame
+        if self.entered_anim_index != Some(self.last_anim_index) {
+            self.enter_frame(self_entity, cur_anim, sheet, maybe_evts.as_deref_mut());
         }
 
         // increment the frame if current time has elapsed the current frame's
         // duration
         let mut anim_ended = false;
+        let mut anim_stopped = false;
+        let mut play_queued = false;
+        let mut queue_time = 0.0;
         while self.cur_time > next_frame_time {
             self.last_frame_start = next_frame_time;
             self.last_anim_index += 1;
-            let anim_len = cur_anim.frame_indices().len();
+            let anim_len = anim_frame_indices.len();
+
+            // keep playback within the frame range if there is one, the
+            // current time is moved back along with the frame so that it stays
+            // in sync with the frame being shown
+            if let Some((range, mode)) = &self.range {
+                let range_end = (*range.end()).min(anim_len - 1);
+                let range_start = (*range.start()).min(range_end);
+                if self.last_anim_index == range_end + 1 {
+                    let range_time: f32 = anim_frame_indices[range_start..=range_end]
+                        .iter()
+                        .map(|frame_index| frames[*frame_index].duration)
+                        .sum();
+                    // ranges without any duration can't loop
+                    let mode = match mode {
+                        RangeMode::Loop if range_time > 0.0 => RangeMode::Loop,
+                        _ => RangeMode::Hold,
+                    };
+                    match mode {
+                        RangeMode::Loop => {
+                            self.cur_time -= range_time;
+                            next_frame_time -= range_time;
+                            self.last_frame_start = next_frame_time;
+                            self.last_anim_index = range_start;
+                        }
+                        RangeMode::Hold => {
+                            self.last_anim_index = range_end;
+                            cur_frame = &frames[anim_frame_indices[range_end]];
+                            self.last_frame_start = next_frame_time - cur_frame.duration;
+                            self.cur_time = next_frame_time;
+                            break;
+                        }
+                    }
+                    cur_frame = &frames[anim_frame_indices[self.last_anim_index]];
+                    next_frame_time += cur_frame.duration;
+                    self.enter_frame(self_entity, cur_anim, sheet, maybe_evts.as_deref_mut());
+                    continue;
+                }
+            }
 
-            // check if the animation has ended and set a flag if so
+            // check if the animation has ended and set a flag if so, queued
+            // animations take priority over the end action
             if self.last_anim_index >= anim_len {
                 anim_ended = true;
-                match cur_anim.end_action {
+                if let Some(evts) = maybe_evts.as_deref_mut() {
+                    evts.finish.send(AnimFinishEvent {
+                        entity: *self_entity,
+                        anim: *self.cur_anim.as_ref().unwrap(),
+                    });
+                }
+                let repeat = self.queued_repeats > 0;
+                play_queued = !repeat && !self.queue.is_empty();
+                let end_action = if repeat {
+                    self.queued_repeats -= 1;
+                    AnimEndAction::Loop
+                } else if play_queued {
+                    AnimEndAction::Stop
+                } else {
+                    match anim_end_action {
+                        AnimEndAction::LoopCount(count) if self.loop_count + 1 < count => {
+                            AnimEndAction::Loop
+                        }
+                        end_action => end_action,
+                    }
+                };
+                match end_action {
                     AnimEndAction::Loop => {
                         self.last_anim_index %= anim_len;
+                        self.loop_count += 1;
+                        if let Some(evts) = maybe_evts.as_deref_mut() {
+                            evts.looped.send(AnimLoopedEvent {
+                                entity: *self_entity,
+                                anim: *self.cur_anim.as_ref().unwrap(),
+                                loop_count: self.loop_count,
+                            });
+                        }
                     }
-                    AnimEndAction::Pause | AnimEndAction::Stop | AnimEndAction::Next(_) => {
+                    _ => {
+                        // the time past the end is played in the next queued
+                        // animation
+                        if play_queued {
+                            queue_time = self.cur_time - next_frame_time;
+                        }
                         self.cur_time = cur_anim.total_time();
                         self.last_anim_index = anim_len - 1;
                         cur_frame = &frames[anim_frame_indices[self.last_anim_index]];
+                        anim_stopped = true;
                         break;
                     }
                 }
@@ -270,6 +939,7 @@ impl SpriteAnimator {
 
             cur_frame = &frames[anim_frame_indices[self.last_anim_index]];
             next_frame_time += cur_frame.duration;
+            self.enter_frame(self_entity, cur_anim, sheet, maybe_evts.as_deref_mut());
         }
 
         // apply the new sprite and anchor in the texture atlas
@@ -278,44 +948,320 @@ impl SpriteAnimator {
 
         // behave according to the sprite end action if the animation ended
         if anim_ended {
-            // send an event letting the program know the animation finished
-            if let Some(evts) = maybe_evts {
-                evts.send(AnimFinishEvent {
+            // play the next queued animation for the rest of the elapsed time,
+            // or act according to end action type if the animation stopped at
+            // its end
+            if play_queued {
+                self.play_next_queued();
+                let time_scale = anim_time_scale * self.time_scale;
+                let delta = if time_scale > 0.0 {
+                    queue_time / time_scale
+                } else {
+                    0.0
+                };
+                return self.animate(delta, self_entity, sheet, sprite, atlas, maybe_evts);
+            } else if anim_stopped {
+                let ended_anim = KeptAnim {
+                    handle: self.cur_anim.unwrap(),
+                    name: Some(cur_anim.name.clone()),
+                };
+                match anim_end_action {
+                    AnimEndAction::Pause | AnimEndAction::Despawn => {
+                        self.finished = true;
+                    }
+                    AnimEndAction::Stop | AnimEndAction::LoopCount(_) | AnimEndAction::Hide => {
+                        self.stop_anim();
+                        self.finished = true;
+                    }
+                    AnimEndAction::Next(anim) => {
+                        self.set_anim(anim);
+                    }
+                    AnimEndAction::ReturnToPrevious => {
+                        match self.prev_anim.as_ref().map(|prev| prev.handle) {
+                            Some(prev_anim) => {
+                                self.set_anim(prev_anim);
+                                self.restart_anim();
+                            }
+                            None => self.stop_anim(),
+                        }
+                    }
+                    AnimEndAction::Loop => {}
+                }
+                // remember the animation that ended, so that state machine
+                // transitions waiting for it can be taken after it's replaced
+                self.ended_anim = Some(ended_anim);
+                return Some(anim_end_action);
+            }
+        }
+        None
+    }
+
+    /// The end action and time scale of the animation that the handle refers
+    /// to, taking the animator's overrides into account
+    fn resolve_settings(&self, handle: &AnimHandle, anim: &Anim) -> (AnimEndAction, f32) {
+        handle
+            .index()
+            .and_then(|index| self.anim_overrides.get(&index))
+            .copied()
+            .unwrap_or((anim.end_action, anim.time_scale))
+    }
+
+    /// Whether the handle refers to the same animation on the spritesheet as
+    /// when the animator played it, which isn't the case if the handle is from
+    /// a different spritesheet, or if the spritesheet was reloaded with its
+    /// animations in a different order or with different frames
+    fn is_resolved_on(&self, handle: &AnimHandle, sheet: &Spritesheet) -> bool {
+        if !handle.is_for_sheet(sheet) {
+            return false;
+        }
+        match (&self.resolved_anim, sheet.get_anim(handle)) {
+            (Some(resolved), Ok(anim)) if resolved.handle == *handle => {
+                resolved.is_for(handle, anim)
+            }
+            _ => true,
+        }
+    }
+
+    /// Set the target time of the current animation to where the transition
+    /// continues from the animation that was playing before it
+    fn apply_transition(&mut self, transition: PendingTransition, sheet: &Spritesheet) {
+        let Ok(from) = sheet.get_anim(&transition.from) else {
+            return;
+        };
+        match transition.mode {
+            TransitionMode::Restart => {}
+            TransitionMode::KeepNormalizedTime => {
+                let total_time = from.total_time();
+                let normalized = if total_time > 0.0 {
+                    transition.time / total_time
+                } else {
+                    0.0
+                };
+                // looping animations continue from the point in their cycle
+                let (from_end_action, _) = self.resolve_settings(&transition.from, from);
+                let normalized = match from_end_action {
+                    AnimEndAction::Loop | AnimEndAction::LoopCount(_) => normalized.fract(),
+                    _ => normalized.min(1.0),
+                };
+                self.target_time = Some(AnimTimestamp::Normalized(normalized));
+            }
+            TransitionMode::KeepAbsoluteTime => {
+                self.target_time = Some(AnimTimestamp::Seconds(transition.time));
+            }
+            TransitionMode::KeepFrameIndex => {
+                self.target_time = Some(AnimTimestamp::Frame(transition.frame));
+            }
+        }
+    }
+
+    /// Seek to the start of the frame at the specified index within the
+    /// current animation, or the last frame if the index is past it
+    fn set_cur_frame(&mut self, frame: usize, sheet: &Spritesheet) {
+        let Some(cur_anim) = self
+            .cur_anim
+            .and_then(|handle| sheet.get_anim(&handle).ok())
+        else {
+            return;
+        };
+        let frame = frame.min(cur_anim.frame_indices().len().saturating_sub(1));
+        self.last_anim_index = frame;
+        self.last_frame_start = cur_anim.frame_indices()[..frame]
+            .iter()
+            .map(|frame_index| sheet.frames[*frame_index].duration)
+            .sum();
+        self.cur_time = self.last_frame_start;
+        self.loop_count = 0;
+    }
+
+    /// Mark the current frame as entered and send the events for entering it,
+    /// including an event for each piece of user data attached to it
+    fn enter_frame(
+        &mut self,
+        self_entity: &Entity,
+        cur_anim: &Anim,
+        sheet: &Spritesheet,
+        maybe_evts: Option<&mut AnimEventWriters>,
+    ) {
+        let prev_anim_index = self.entered_anim_index.replace(self.last_anim_index);
+        let Some(evts) = maybe_evts else {
+            return;
+        };
+        let anim = *self.cur_anim.as_ref().unwrap();
+
+        // no frame has been entered since the animation was set or restarted,
+        // so it just started
+        match prev_anim_index {
+            None => {
+                evts.started.send(AnimStartedEvent {
                     entity: *self_entity,
-                    anim: *self.cur_anim.as_ref().unwrap(),
+                    anim,
                 });
             }
-
-            // act according to end action type
-            match cur_anim.end_action {
-                AnimEndAction::Pause => {
-                    self.time_scale = 0.0;
-                }
-                AnimEndAction::Stop => {
-                    self.stop_anim();
-                }
-                AnimEndAction::Next(anim) => {
-                    self.set_anim(anim);
-                }
-                _ => {}
+            Some(old_frame) if old_frame != self.last_anim_index => {
+                evts.frame_changed.send(AnimFrameChangedEvent {
+                    entity: *self_entity,
+                    anim,
+                    old_frame,
+                    new_frame: self.last_anim_index,
+                });
             }
+            _ => {}
+        }
+
+        let frame = &sheet.frames[cur_anim.frame_indices()[self.last_anim_index]];
+        let tag_data = cur_anim
+            .user_data
+            .as_ref()
+            .filter(|_| self.last_anim_index == 0);
+        for payload in tag_data.into_iter().chain(frame.user_data.iter()) {
+            evts.frame.send(AnimFrameEvent {
+                entity: *self_entity,
+                anim,
+                frame: self.last_anim_index,
+                payload: payload.clone(),
+            });
         }
     }
 
+    /// The index of the frame in [`Spritesheet::frames`] that is currently
+    /// being shown, if an animation is playing
+    pub(crate) fn cur_sheet_frame(&self, sheet: &Spritesheet) -> Option<usize> {
+        let cur_anim = sheet.get_anim(self.cur_anim.as_ref()?).ok()?;
+        cur_anim.frame_indices().get(self.last_anim_index).copied()
+    }
+
     fn reset_persistent_data(&mut self) {
         self.restart_anim();
     }
 }
 
+impl ResolvedAnim {
+    fn new(handle: AnimHandle, anim: &Anim) -> Self {
+        Self {
+            handle,
+            name: anim.name.clone(),
+            frame_count: anim.frame_indices().len(),
+            total_time: anim.total_time(),
+        }
+    }
+
+    /// Whether the handle still refers to the same animation, with the same
+    /// frames, that the animator played
+    fn is_for(&self, handle: &AnimHandle, anim: &Anim) -> bool {
+        self.handle == *handle
+            && self.name == anim.name
+            && self.frame_count == anim.frame_indices().len()
+            && self.total_time == anim.total_time()
+    }
+}
+
+impl KeptAnim {
+    fn new(handle: AnimHandle) -> Self {
+        Self { handle, name: None }
+    }
+
+    /// Remember the name of the animation if the spritesheet has it, or remap
+    /// the handle to the animation with the remembered name if the handle
+    /// refers to a different animation. Returns false if the spritesheet
+    /// doesn't have the animation with the remembered name
+    fn resolve(&mut self, sheet: &Spritesheet) -> bool {
+        match (&self.name, sheet.get_anim(&self.handle)) {
+            (None, Ok(anim)) => {
+                self.name = Some(anim.name.clone());
+                true
+            }
+            // handles from another spritesheet can't be remapped before their
+            // names are known, they're checked when they're played
+            (None, Err(_)) => true,
+            (Some(name), Ok(anim)) if anim.name == *name => true,
+            (Some(name), _) => {
+                let handle = sheet.get_anim_handle(name);
+                if !handle.is_valid() {
+                    return false;
+                }
+                self.handle = handle;
+                true
+            }
+        }
+    }
+}
+
+impl SliceColliders {
+    /// All the slices that are active on the current frame
+    pub fn iter(&self) -> impl Iterator<Item = &SliceCollider> {
+        self.colliders.iter()
+    }
+
+    /// The slice with the specified name, if it's active on the current frame
+    pub fn get<T: AsRef<str>>(&self, name: T) -> Option<&SliceCollider> {
+        self.colliders
+            .iter()
+            .find(|collider| collider.name == name.as_ref())
+    }
+
+    /// Whether or not there are any slices active on the current frame
+    pub fn is_empty(&self) -> bool {
+        self.colliders.is_empty()
+    }
+
+    /// Whether or not the colliders need to be updated to match the specified
+    /// frame of the spritesheet and sprite flip
+    fn is_stale(
+        &self,
+        sheet: &Spritesheet,
+        frame: Option<usize>,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> bool {
+        self.sheet != Some(sheet.sheet_id()) || self.frame != frame || self.flip != (flip_x, flip_y)
+    }
+
+    /// Make sure that the colliders are updated on the next animation update,
+    /// such as when the slices of the spritesheet changed
+    pub(crate) fn mark_stale(&mut self) {
+        self.sheet = None;
+    }
+
+    /// Update the colliders to the slices active on the specified frame of
+    /// the spritesheet, mirrored according to the sprite flip
+    pub fn update(
+        &mut self,
+        sheet: &Spritesheet,
+        frame: Option<usize>,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        self.sheet = Some(sheet.sheet_id());
+        self.frame = frame;
+        self.flip = (flip_x, flip_y);
+        self.colliders.clear();
+        let Some(frame) = frame else {
+            return;
+        };
+        let flip = Vec2::new(
+            if flip_x { -1.0 } else { 1.0 },
+            if flip_y { -1.0 } else { 1.0 },
+        );
+        for (slice, key) in sheet.frame_slices(frame) {
+            self.colliders.push(SliceCollider {
+                name: slice.name.clone(),
+                rect: Rect::from_corners(key.rect.min * flip, key.rect.max * flip),
+                pivot: key.pivot.map(|pivot| pivot * flip),
+            });
+        }
+    }
+}
+
 // Systems: --------------------------------------------------------------------
 
 /// system that runs in the specified plugin schedule to update all the animated spritesheets in the
 /// ecs world, some paramaters can be tweaked globally with the [`SpriteAnimController`] resource
 pub fn animate_sprites(
+    mut commands: Commands,
     time: Res<Time>,
     spritesheet_assets: Res<Assets<Spritesheet>>,
     anim_controller: Res<SpriteAnimController>,
-    mut events: EventWriter<AnimFinishEvent>,
+    mut events: AnimEventWriters,
     mut query: Query<(
         Entity,
         &mut Sprite,
@@ -323,6 +1269,8 @@ pub fn animate_sprites(
         &mut SpriteAnimator,
         &Handle<Spritesheet>,
         Option<&AnimEventSender>,
+        Option<&mut SliceColliders>,
+        Option<&mut Visibility>,
     )>,
 ) {
     if !anim_controller.is_active {
@@ -330,8 +1278,16 @@ pub fn animate_sprites(
     }
     let time_scale = anim_controller.global_time_scale;
     let dt = time.delta_seconds() * time_scale;
-    for (entity, mut sprite, mut atlas, mut sprite_animator, sheet_handle, maybe_evt_send) in
-        &mut query
+    for (
+        entity,
+        mut sprite,
+        mut atlas,
+        mut sprite_animator,
+        sheet_handle,
+        maybe_evt_send,
+        maybe_colliders,
+        maybe_visibility,
+    ) in &mut query
     {
         if let Some(sheet) = spritesheet_assets.get(sheet_handle) {
             // only pass in the event writer if the entity has the event sender component
@@ -340,7 +1296,30 @@ pub fn animate_sprites(
             } else {
                 None
             };
-            sprite_animator.animate(dt, &entity, sheet, &mut sprite, &mut atlas, maybe_evts);
+            let end_action =
+                sprite_animator.animate(dt, &entity, sheet, &mut sprite, &mut atlas, maybe_evts);
+
+            // handle the end actions that affect the entity
+            match end_action {
+                Some(AnimEndAction::Despawn) => {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+                Some(AnimEndAction::Hide) => {
+                    if let Some(mut visibility) = maybe_visibility {
+                        *visibility = Visibility::Hidden;
+                    }
+                }
+                _ => {}
+            }
+
+            // keep the slice colliders in sync with the frame being shown
+            if let Some(mut colliders) = maybe_colliders {
+                let frame = sprite_animator.cur_sheet_frame(sheet);
+                if colliders.is_stale(sheet, frame, sprite.flip_x, sprite.flip_y) {
+                    colliders.update(sheet, frame, sprite.flip_x, sprite.flip_y);
+                }
+            }
         }
     }
 }
@@ -441,3 +1420,710 @@ pub fn cloned_flipped_anchor(anchor: Anchor, flip_x: bool, flip_y: bool) -> Anch
         _ => anchor,
     }
 }
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::aseprite_data::SpritesheetData;
+    use bevy::ecs::system::{RunSystemOnce, SystemState};
+    use std::time::Duration;
+
+    /// A spritesheet with six 100ms frames, whose atlas indices match their
+    /// frame indices, and a frame tag for each of the specified name, first
+    /// frame, last frame and direction
+    pub(crate) fn test_sheet(tags: &[(&str, usize, usize, &str)]) -> Spritesheet {
+        let mut sheet = Spritesheet::default();
+        sheet.copy_from(&test_data(tags), &Anchor::Center);
+        sheet
+    }
+
+    /// The spritesheet data that [`test_sheet`] is generated from, with 8x8
+    /// frames that aren't trimmed
+    fn test_data(tags: &[(&str, usize, usize, &str)]) -> SpritesheetData {
+        let frames: Vec<_> = (0..6)
+            .map(|i| {
+                serde_json::json!({
+                    "filename": format!("frame {}", i),
+                    "frame": { "x": i * 8, "y": 0, "w": 8, "h": 8 },
+                    "rotated": false,
+                    "trimmed": false,
+                    "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 },
+                    "sourceSize": { "w": 8, "h": 8 },
+                    "duration": 100,
+                })
+            })
+            .collect();
+        let frame_tags: Vec<_> = tags
+            .iter()
+            .map(|(name, from, to, direction)| {
+                serde_json::json!({
+                    "name": name,
+                    "from": from,
+                    "to": to,
+                    "direction": direction,
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "frames": frames,
+            "meta": {
+                "app": "test",
+                "version": "1",
+                "image": "test.png",
+                "format": "RGBA8888",
+                "size": { "w": 48, "h": 8 },
+                "scale": "1",
+                "frameTags": frame_tags,
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Animate over the specified elapsed time and get the atlas index of the
+    /// frame being shown
+    pub(crate) fn step(animator: &mut SpriteAnimator, sheet: &Spritesheet, delta: f32) -> usize {
+        let mut sprite = Sprite::default();
+        let mut atlas = TextureAtlas::default();
+        animator.animate(
+            delta,
+            &Entity::PLACEHOLDER,
+            sheet,
+            &mut sprite,
+            &mut atlas,
+            None,
+        );
+        atlas.index
+    }
+
+    /// A world with the resources of the animation events
+    fn event_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<AnimFinishEvent>>();
+        world.init_resource::<Events<AnimFrameEvent>>();
+        world.init_resource::<Events<AnimStartedEvent>>();
+        world.init_resource::<Events<AnimLoopedEvent>>();
+        world.init_resource::<Events<AnimFrameChangedEvent>>();
+        world
+    }
+
+    /// Animate over the specified elapsed time like [`step`], sending the
+    /// animation events into the world
+    fn step_with_events(
+        animator: &mut SpriteAnimator,
+        sheet: &Spritesheet,
+        world: &mut World,
+        delta: f32,
+    ) -> usize {
+        let mut state = SystemState::<AnimEventWriters>::new(world);
+        let mut evts = state.get_mut(world);
+        let mut sprite = Sprite::default();
+        let mut atlas = TextureAtlas::default();
+        animator.animate(
+            delta,
+            &Entity::PLACEHOLDER,
+            sheet,
+            &mut sprite,
+            &mut atlas,
+            Some(&mut evts),
+        );
+        atlas.index
+    }
+
+    /// Take the events of the specified type that were sent since they were
+    /// last taken
+    fn take_events<E: Event>(world: &mut World) -> Vec<E> {
+        world.resource_mut::<Events<E>>().drain().collect()
+    }
+
+    /// The atlas indices shown at the middle of each frame time while playing
+    /// the specified animation from the start
+    fn play_frames(sheet: &Spritesheet, name: &str, count: usize) -> Vec<usize> {
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle(name));
+        let mut frames = vec![step(&mut animator, sheet, 0.05)];
+        while frames.len() < count {
+            frames.push(step(&mut animator, sheet, 0.1));
+        }
+        frames
+    }
+
+    #[test]
+    fn set_anim_after_stop_advances() {
+        let mut sheet = test_sheet(&[("attack", 0, 3, "forward"), ("idle", 4, 5, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        sheet.get_anim_mut(&attack).unwrap().end_action = AnimEndAction::Stop;
+
+        // play the animation past its end so that it stops
+        let mut animator = SpriteAnimator::from_anim(attack);
+        step(&mut animator, &sheet, 0.05);
+        step(&mut animator, &sheet, 0.4);
+        assert!(animator.cur_anim().is_none());
+        assert!(animator.is_finished());
+
+        animator.set_anim(sheet.get_anim_handle("idle"));
+        assert!(!animator.is_finished());
+        assert_eq!(step(&mut animator, &sheet, 0.05), 4);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 5);
+    }
+
+    #[test]
+    fn frame_order_follows_direction() {
+        let sheet = test_sheet(&[
+            ("forward", 0, 3, "forward"),
+            ("reverse", 0, 3, "reverse"),
+            ("pingpong", 0, 3, "pingpong"),
+            ("pingpong_reverse", 0, 3, "pingpong_reverse"),
+        ]);
+        assert_eq!(play_frames(&sheet, "forward", 6), [0, 1, 2, 3, 0, 1]);
+        assert_eq!(play_frames(&sheet, "reverse", 6), [3, 2, 1, 0, 3, 2]);
+        assert_eq!(play_frames(&sheet, "pingpong", 8), [0, 1, 2, 3, 2, 1, 0, 1]);
+        assert_eq!(
+            play_frames(&sheet, "pingpong_reverse", 8),
+            [3, 2, 1, 0, 1, 2, 3, 2]
+        );
+    }
+
+    #[test]
+    fn transition_modes_keep_time() {
+        let sheet = test_sheet(&[("walk", 0, 3, "forward"), ("aim", 4, 5, "forward")]);
+        let walk = sheet.get_anim_handle("walk");
+        let aim = sheet.get_anim_handle("aim");
+
+        // switch from 0.25 seconds into the walk animation, on its third frame
+        let transition = |mode: TransitionMode| {
+            let mut animator = SpriteAnimator::from_anim(walk);
+            step(&mut animator, &sheet, 0.05);
+            step(&mut animator, &sheet, 0.2);
+            animator.set_anim_with(aim, mode);
+            let frame = step(&mut animator, &sheet, 0.05);
+            (frame, animator.cur_time())
+        };
+
+        assert_eq!(transition(TransitionMode::Restart), (4, 0.05));
+        // 62.5% of the way through the animation
+        assert_eq!(transition(TransitionMode::KeepNormalizedTime), (5, 0.125));
+        // the aim animation loops after 0.2 seconds
+        let (frame, time) = transition(TransitionMode::KeepAbsoluteTime);
+        assert_eq!(frame, 4);
+        assert!((time - 0.25).abs() < 1e-6);
+        // the aim animation doesn't have a third frame
+        assert_eq!(transition(TransitionMode::KeepFrameIndex), (5, 0.1));
+    }
+
+    #[test]
+    fn seek_to_frame() {
+        let sheet = test_sheet(&[("walk", 1, 4, "forward")]);
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("walk"));
+        step(&mut animator, &sheet, 0.05);
+
+        animator.set_cur_time(AnimTimestamp::Frame(2));
+        assert_eq!(step(&mut animator, &sheet, 0.05), 3);
+        assert_eq!(animator.cur_frame_index(), Some(2));
+        assert_eq!(animator.cur_atlas_index(&sheet), Some(3));
+        assert_eq!(animator.time_in_frame(), 0.0);
+        assert!((animator.time_remaining(&sheet) - 0.2).abs() < 1e-6);
+
+        // playback continues from the frame that was seeked to
+        assert_eq!(step(&mut animator, &sheet, 0.15), 4);
+        assert_eq!(animator.cur_frame_index(), Some(3));
+        assert!((animator.time_in_frame() - 0.05).abs() < 1e-6);
+
+        // frames past the end of the animation seek to the last frame
+        animator.set_cur_time(AnimTimestamp::Frame(10));
+        assert_eq!(step(&mut animator, &sheet, 0.05), 4);
+        assert_eq!(animator.cur_frame_index(), Some(3));
+    }
+
+    #[test]
+    fn range_loop_and_hold() {
+        let sheet = test_sheet(&[("charge", 0, 5, "forward")]);
+        let charge = sheet.get_anim_handle("charge");
+
+        // play the animation with a range, then release it after six frames
+        let play = |mode: RangeMode| {
+            let mut animator = SpriteAnimator::from_anim(charge);
+            animator.play_range(2..=3, mode);
+            let mut frames = vec![step(&mut animator, &sheet, 0.05)];
+            for _ in 0..5 {
+                frames.push(step(&mut animator, &sheet, 0.1));
+            }
+            animator.release_range();
+            for _ in 0..2 {
+                frames.push(step(&mut animator, &sheet, 0.1));
+            }
+            frames
+        };
+
+        assert_eq!(play(RangeMode::Loop), [0, 1, 2, 3, 2, 3, 4, 5]);
+        assert_eq!(play(RangeMode::Hold), [0, 1, 2, 3, 3, 3, 4, 5]);
+    }
+
+    #[test]
+    fn seek_recomputes_loop_count() {
+        let mut sheet = test_sheet(&[("walk", 0, 3, "forward")]);
+        let walk = sheet.get_anim_handle("walk");
+        sheet.get_anim_mut(&walk).unwrap().end_action = AnimEndAction::LoopCount(2);
+        let mut animator = SpriteAnimator::from_anim(walk);
+
+        // seek into the second loop
+        animator.set_cur_time(AnimTimestamp::Seconds(0.65));
+        assert_eq!(step(&mut animator, &sheet, 0.0), 2);
+        assert_eq!(animator.loop_count(), 1);
+        assert!((animator.time_remaining(&sheet) - 0.15).abs() < 1e-6);
+
+        // seeking back into the first loop plays both loops again
+        animator.set_cur_time(AnimTimestamp::Seconds(0.05));
+        assert_eq!(step(&mut animator, &sheet, 0.0), 0);
+        assert_eq!(animator.loop_count(), 0);
+        assert!((animator.time_remaining(&sheet) - 0.35).abs() < 1e-6);
+        step(&mut animator, &sheet, 0.4);
+        assert_eq!(animator.loop_count(), 1);
+        assert!(animator.cur_anim().is_some());
+    }
+
+    #[test]
+    fn reloading_shorter_anim_keeps_time() {
+        let mut sheet = test_sheet(&[("walk", 0, 5, "forward")]);
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("walk"));
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(step(&mut animator, &sheet, 0.4), 4);
+
+        // rebuild the spritesheet with the tag shortened to two frames, the
+        // animator continues at the same time, in the third loop
+        sheet.copy_from(&test_data(&[("walk", 0, 1, "forward")]), &Anchor::Center);
+        assert_eq!(step(&mut animator, &sheet, 0.0), 0);
+        assert_eq!(animator.cur_frame_index(), Some(0));
+        assert_eq!(animator.loop_count(), 2);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 1);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 0);
+    }
+
+    #[test]
+    fn handles_only_match_their_sheet() {
+        let sheet = test_sheet(&[("idle", 0, 3, "forward")]);
+        let other = test_sheet(&[("idle", 0, 3, "forward")]);
+        let clone = sheet.clone();
+        let idle = sheet.get_anim_handle("idle");
+
+        // index handles match the handles of any spritesheet
+        assert!(AnimHandle::from_index(0).matches(&idle));
+        assert_ne!(AnimHandle::from_index(0), idle);
+        assert!(!other.get_anim_handle("idle").matches(&idle));
+        assert!(other.get_anim(&idle).is_err());
+        assert!(other.get_anim(&AnimHandle::from_index(0)).is_ok());
+
+        // clones don't accept the handles of the original spritesheet
+        assert!(clone.get_anim(&idle).is_err());
+        assert!(clone.get_anim(&idle.retarget(&sheet, &clone)).is_ok());
+
+        // so setting the same animation by index doesn't restart it
+        let mut animator = SpriteAnimator::from_anim(idle);
+        step(&mut animator, &sheet, 0.15);
+        animator.set_anim_index(0);
+        assert_eq!(step(&mut animator, &sheet, 0.0), 1);
+    }
+
+    /// A spritesheet with two frame attack, recover and idle animations,
+    /// where the idle animation pauses at its end
+    fn queue_sheet() -> Spritesheet {
+        let mut sheet = test_sheet(&[
+            ("attack", 0, 1, "forward"),
+            ("recover", 2, 3, "forward"),
+            ("idle", 4, 5, "forward"),
+        ]);
+        let idle = sheet.get_anim_handle("idle");
+        sheet.get_anim_mut(&idle).unwrap().end_action = AnimEndAction::Pause;
+        sheet
+    }
+
+    #[test]
+    fn queue_plays_in_order() {
+        let sheet = queue_sheet();
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("attack"));
+        animator.queue_anim_repeated(sheet.get_anim_handle("recover"), 2);
+        animator.queue_anim(sheet.get_anim_handle("idle"));
+        assert_eq!(animator.queue_len(), 2);
+
+        // each queued animation starts as soon as the one before it ends, and
+        // the last one uses its own end action
+        let mut frames = vec![step(&mut animator, &sheet, 0.05)];
+        for _ in 0..9 {
+            frames.push(step(&mut animator, &sheet, 0.1));
+        }
+        assert_eq!(frames, [0, 1, 2, 3, 2, 3, 4, 5, 5, 5]);
+        assert_eq!(animator.queue_len(), 0);
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn clear_queue_keeps_end_action() {
+        let sheet = queue_sheet();
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("attack"));
+        animator.queue_anim(sheet.get_anim_handle("recover"));
+        step(&mut animator, &sheet, 0.05);
+        animator.clear_queue();
+        assert_eq!(animator.queue_len(), 0);
+
+        // the attack animation loops instead of moving on
+        assert_eq!(step(&mut animator, &sheet, 0.1), 1);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 0);
+    }
+
+    #[test]
+    fn queue_after_finished_anim() {
+        let sheet = queue_sheet();
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("idle"));
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(step(&mut animator, &sheet, 0.2), 5);
+        assert!(animator.is_finished());
+
+        animator.queue_anim(sheet.get_anim_handle("attack"));
+        assert_eq!(step(&mut animator, &sheet, 0.05), 0);
+        assert!(!animator.is_finished());
+    }
+
+    #[test]
+    fn pause_end_action_resumes_from_start() {
+        let sheet = queue_sheet();
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("idle"));
+        step(&mut animator, &sheet, 0.05);
+        assert!(!animator.is_paused());
+        assert_eq!(step(&mut animator, &sheet, 0.2), 5);
+        assert!(animator.is_paused());
+        assert_eq!(step(&mut animator, &sheet, 0.1), 5);
+
+        // resuming plays the animation again instead of staying at its end
+        animator.resume();
+        assert!(!animator.is_paused());
+        assert!(!animator.is_finished());
+        assert_eq!(step(&mut animator, &sheet, 0.05), 4);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 5);
+    }
+
+    #[test]
+    fn queue_sends_finish_event_per_clip() {
+        let sheet = queue_sheet();
+        let attack = sheet.get_anim_handle("attack");
+        let recover = sheet.get_anim_handle("recover");
+        let idle = sheet.get_anim_handle("idle");
+        let mut world = event_world();
+        let mut animator = SpriteAnimator::from_anim(attack);
+        animator.queue_anim_repeated(recover, 2);
+        animator.queue_anim(idle);
+
+        // the whole queue is played in a single update
+        step_with_events(&mut animator, &sheet, &mut world, 0.05);
+        assert_eq!(step_with_events(&mut animator, &sheet, &mut world, 1.0), 5);
+        let finished: Vec<_> = take_events::<AnimFinishEvent>(&mut world)
+            .into_iter()
+            .map(|evt| evt.anim)
+            .collect();
+        assert_eq!(finished, [attack, recover, recover, idle]);
+    }
+
+    #[test]
+    fn return_to_previous_anim() {
+        let mut sheet = test_sheet(&[
+            ("idle", 0, 1, "forward"),
+            ("attack", 2, 3, "forward"),
+            ("hurt", 4, 5, "forward"),
+        ]);
+        let idle = sheet.get_anim_handle("idle");
+        let attack = sheet.get_anim_handle("attack");
+        let hurt = sheet.get_anim_handle("hurt");
+        sheet.get_anim_mut(&attack).unwrap().end_action = AnimEndAction::Stop;
+        sheet.get_anim_mut(&hurt).unwrap().end_action = AnimEndAction::ReturnToPrevious;
+
+        // the interrupted animation is played again from the start
+        let mut animator = SpriteAnimator::from_anim(idle);
+        step(&mut animator, &sheet, 0.15);
+        animator.set_anim(hurt);
+        step(&mut animator, &sheet, 0.05);
+        step(&mut animator, &sheet, 0.2);
+        assert!(animator.is_cur_anim(idle));
+        assert_eq!(animator.cur_time(), 0.0);
+        assert_eq!(step(&mut animator, &sheet, 0.05), 0);
+
+        // there's nothing to return to once an animation was stopped
+        animator.set_anim(attack);
+        step(&mut animator, &sheet, 0.05);
+        step(&mut animator, &sheet, 0.2);
+        assert!(animator.cur_anim().is_none());
+        animator.set_anim(hurt);
+        step(&mut animator, &sheet, 0.05);
+        step(&mut animator, &sheet, 0.2);
+        assert!(animator.cur_anim().is_none());
+    }
+
+    #[test]
+    fn reloading_reordered_anims_keeps_queue_and_previous() {
+        let mut sheet = test_sheet(&[
+            ("attack", 0, 1, "forward"),
+            ("recover", 2, 3, "forward"),
+            ("hurt", 4, 5, "forward"),
+        ]);
+        let reordered = test_data(&[
+            ("recover", 0, 1, "forward"),
+            ("hurt", 2, 3, "forward"),
+            ("attack", 4, 5, "forward"),
+        ]);
+
+        // the queued animation is still played after the reload
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("attack"));
+        animator.queue_anim(sheet.get_anim_handle("recover"));
+        step(&mut animator, &sheet, 0.05);
+        sheet.copy_from(&reordered, &Anchor::Center);
+        assert_eq!(step(&mut animator, &sheet, 0.0), 4);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 5);
+        assert_eq!(step(&mut animator, &sheet, 0.1), 0);
+        assert!(animator.is_cur_anim(sheet.get_anim_handle("recover")));
+
+        // and the interrupted animation is still returned to
+        let mut sheet = test_sheet(&[
+            ("attack", 0, 1, "forward"),
+            ("recover", 2, 3, "forward"),
+            ("hurt", 4, 5, "forward"),
+        ]);
+        let mut animator = SpriteAnimator::from_anim(sheet.get_anim_handle("attack"));
+        step(&mut animator, &sheet, 0.15);
+        animator.set_anim(sheet.get_anim_handle("hurt"));
+        step(&mut animator, &sheet, 0.05);
+        sheet.copy_from(&reordered, &Anchor::Center);
+        let hurt = sheet.get_anim_handle("hurt");
+        sheet.get_anim_mut(&hurt).unwrap().end_action = AnimEndAction::ReturnToPrevious;
+        assert_eq!(step(&mut animator, &sheet, 0.0), 2);
+        step(&mut animator, &sheet, 0.2);
+        assert!(animator.is_cur_anim(sheet.get_anim_handle("attack")));
+        assert_eq!(step(&mut animator, &sheet, 0.05), 4);
+    }
+
+    #[test]
+    fn despawn_and_hide_end_actions() {
+        let mut sheet = test_sheet(&[("explode", 0, 1, "forward"), ("fade", 2, 3, "forward")]);
+        let explode = sheet.get_anim_handle("explode");
+        let fade = sheet.get_anim_handle("fade");
+        sheet.get_anim_mut(&explode).unwrap().end_action = AnimEndAction::Despawn;
+        sheet.get_anim_mut(&fade).unwrap().end_action = AnimEndAction::Hide;
+
+        let mut world = event_world();
+        world.init_resource::<Time>();
+        world.init_resource::<SpriteAnimController>();
+        let mut sheets = Assets::<Spritesheet>::default();
+        let sheet_handle = sheets.add(sheet);
+        world.insert_resource(sheets);
+        let mut spawn = |anim: AnimHandle| {
+            world
+                .spawn((
+                    Sprite::default(),
+                    TextureAtlas::default(),
+                    SpriteAnimator::from_anim(anim),
+                    sheet_handle.clone(),
+                    Visibility::Visible,
+                ))
+                .id()
+        };
+        let explosion = spawn(explode);
+        let ghost = spawn(fade);
+        let update = |world: &mut World, delta: f32| {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(delta));
+            world.run_system_once(animate_sprites);
+        };
+
+        update(&mut world, 0.05);
+        assert!(world.get_entity(explosion).is_some());
+        assert_eq!(world.get::<Visibility>(ghost), Some(&Visibility::Visible));
+
+        update(&mut world, 0.2);
+        assert!(world.get_entity(explosion).is_none());
+        assert_eq!(world.get::<Visibility>(ghost), Some(&Visibility::Hidden));
+        let animator = world.get::<SpriteAnimator>(ghost).unwrap();
+        assert!(animator.cur_anim().is_none());
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn slice_colliders_follow_sheet() {
+        let sheet = test_sheet(&[("idle", 0, 1, "forward")]);
+        let other = test_sheet(&[("idle", 0, 1, "forward")]);
+        let mut colliders = SliceColliders::default();
+        assert!(colliders.is_stale(&sheet, None, false, false));
+
+        colliders.update(&sheet, Some(0), false, false);
+        assert!(!colliders.is_stale(&sheet, Some(0), false, false));
+        assert!(colliders.is_stale(&sheet, Some(1), false, false));
+        assert!(colliders.is_stale(&sheet, Some(0), true, false));
+
+        // the same frame of a different spritesheet can have different slices
+        assert!(colliders.is_stale(&other, Some(0), false, false));
+
+        // as can the same spritesheet once it's rebuilt
+        colliders.mark_stale();
+        assert!(colliders.is_stale(&sheet, Some(0), false, false));
+    }
+
+    #[test]
+    fn slice_colliders_match_anchor_and_flip() {
+        // a 3x4 slice 1 pixel from the left and 2 pixels from the top of the
+        // frames, with its pivot 1 pixel into it
+        let mut data = test_data(&[]);
+        data.meta.slices = serde_json::from_value(serde_json::json!([{
+            "name": "hitbox",
+            "color": "#0000ffff",
+            "keys": [{
+                "frame": 0,
+                "bounds": { "x": 1, "y": 2, "w": 3, "h": 4 },
+                "pivot": { "x": 1, "y": 1 },
+            }],
+        }]))
+        .unwrap();
+        let collider = |anchor: Anchor, flip_x: bool, flip_y: bool| {
+            let mut sheet = Spritesheet::default();
+            sheet.copy_from(&data, &anchor);
+            let mut colliders = SliceColliders::default();
+            colliders.update(&sheet, Some(0), flip_x, flip_y);
+            let collider = colliders.get("hitbox").unwrap().clone();
+            (collider.rect, collider.pivot.unwrap())
+        };
+        let rect = |min: (f32, f32), max: (f32, f32)| {
+            Rect::from_corners(Vec2::new(min.0, min.1), Vec2::new(max.0, max.1))
+        };
+
+        // y points up from the anchor, which is the middle of the frame
+        assert_eq!(
+            collider(Anchor::Center, false, false),
+            (rect((-3.0, -2.0), (0.0, 2.0)), Vec2::new(-2.0, 1.0))
+        );
+        // flipping mirrors the slice around the anchor
+        assert_eq!(
+            collider(Anchor::Center, true, false),
+            (rect((0.0, -2.0), (3.0, 2.0)), Vec2::new(2.0, 1.0))
+        );
+        assert_eq!(
+            collider(Anchor::Center, false, true),
+            (rect((-3.0, -2.0), (0.0, 2.0)), Vec2::new(-2.0, -1.0))
+        );
+
+        // the frame anchor math puts the top left corner of untrimmed frames
+        // at the origin for a bottom left anchor, and the slice stays in the
+        // same place on the frame
+        let sheet = {
+            let mut sheet = Spritesheet::default();
+            sheet.copy_from(&data, &Anchor::BottomLeft);
+            sheet
+        };
+        assert_eq!(sheet.frames[0].anchor, Anchor::Custom(Vec2::new(-0.5, 0.5)));
+        assert_eq!(
+            collider(Anchor::BottomLeft, false, false),
+            (rect((1.0, -6.0), (4.0, -2.0)), Vec2::new(2.0, -3.0))
+        );
+        assert_eq!(
+            collider(Anchor::BottomLeft, true, true),
+            (rect((-4.0, 2.0), (-1.0, 6.0)), Vec2::new(-2.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn overrides_only_affect_one_animator() {
+        let sheet = test_sheet(&[("attack", 0, 1, "forward"), ("idle", 2, 3, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let idle = sheet.get_anim_handle("idle");
+        let mut overridden = SpriteAnimator::from_anim(attack);
+        overridden.set_anim_override(attack, AnimEndAction::Next(idle), 2.0);
+        let mut plain = SpriteAnimator::from_anim(attack);
+
+        // the override plays the attack twice as fast and then moves on
+        step(&mut overridden, &sheet, 0.025);
+        step(&mut plain, &sheet, 0.025);
+        assert_eq!(step(&mut overridden, &sheet, 0.05), 1);
+        assert_eq!(step(&mut plain, &sheet, 0.05), 0);
+        step(&mut overridden, &sheet, 0.05);
+        step(&mut plain, &sheet, 0.05);
+        assert!(overridden.is_cur_anim(idle));
+        assert_eq!(step(&mut overridden, &sheet, 0.0), 2);
+        assert!(plain.is_cur_anim(attack));
+
+        overridden.clear_anim_overrides();
+        assert_eq!(
+            overridden.anim_settings(&attack, &sheet),
+            Some((AnimEndAction::Loop, 1.0))
+        );
+    }
+
+    #[test]
+    fn frame_events_follow_user_data() {
+        let mut sheet = test_sheet(&[("attack", 0, 3, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let user_data = |text: &str| UserData {
+            text: text.to_string(),
+            color: None,
+        };
+        sheet.get_anim_mut(&attack).unwrap().user_data = Some(user_data("swing"));
+        sheet.frames[2].user_data = vec![user_data("step"), user_data("dust")];
+
+        let mut world = event_world();
+        let mut animator = SpriteAnimator::from_anim(attack);
+        let mut play = |delta: f32| {
+            step_with_events(&mut animator, &sheet, &mut world, delta);
+            take_events::<AnimFrameEvent>(&mut world)
+                .into_iter()
+                .map(|evt| (evt.frame, evt.payload.text))
+                .collect::<Vec<_>>()
+        };
+        let events = |list: &[(usize, &str)]| {
+            list.iter()
+                .map(|(frame, text)| (*frame, text.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        // the tag's user data is sent on the first frame, and each cel's on
+        // its own frame
+        assert_eq!(play(0.05), events(&[(0, "swing")]));
+        assert_eq!(play(0.1), events(&[]));
+        assert_eq!(play(0.1), events(&[(2, "step"), (2, "dust")]));
+        assert_eq!(play(0.0), events(&[]));
+
+        // every frame passed in a single update sends its events
+        assert_eq!(play(0.4), events(&[(0, "swing"), (2, "step"), (2, "dust")]));
+    }
+
+    #[test]
+    fn playback_events() {
+        let sheet = test_sheet(&[("attack", 0, 3, "forward"), ("idle", 4, 5, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let mut world = event_world();
+        let mut animator = SpriteAnimator::from_anim(attack);
+        let mut play = |animator: &mut SpriteAnimator, delta: f32| {
+            step_with_events(animator, &sheet, &mut world, delta);
+            (
+                take_events::<AnimStartedEvent>(&mut world).len(),
+                take_events::<AnimFrameChangedEvent>(&mut world)
+                    .into_iter()
+                    .map(|evt| (evt.old_frame, evt.new_frame))
+                    .collect::<Vec<_>>(),
+                take_events::<AnimLoopedEvent>(&mut world)
+                    .into_iter()
+                    .map(|evt| evt.loop_count)
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(play(&mut animator, 0.05), (1, vec![], vec![]));
+        assert_eq!(play(&mut animator, 0.1), (0, vec![(0, 1)], vec![]));
+        assert_eq!(play(&mut animator, 0.0), (0, vec![], vec![]));
+
+        // every frame change and loop in a single update is sent
+        assert_eq!(
+            play(&mut animator, 0.4),
+            (0, vec![(1, 2), (2, 3), (3, 0), (0, 1)], vec![1])
+        );
+
+        // restarting or changing the animation starts it again
+        animator.restart_anim();
+        assert_eq!(play(&mut animator, 0.05), (1, vec![], vec![]));
+        animator.set_anim(sheet.get_anim_handle("idle"));
+        assert_eq!(play(&mut animator, 0.05), (1, vec![], vec![]));
+    }
+}
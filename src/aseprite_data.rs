@@ -1,20 +1,43 @@
+use crate::{assets::SpritesheetLoaderSettings, sprite::AnimConfig};
 use bevy::prelude::*;
-use serde::Deserialize;
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::fmt;
 
 // Struct Definitions: ---------------------------------------------------------
 
-/// A container to hold the json data output from aseprite
+/// A container to hold the json data output from aseprite. Both the "Array"
+/// and "Hash" json export layouts are supported, frames are kept in the order
+/// that they appear in the file either way
 #[derive(Deserialize, Reflect, Debug, Asset)]
 // #[uuid = "73461c8f-e760-4fb8-8492-37d5387fca7b"]
 pub struct SpritesheetData {
+    #[serde(deserialize_with = "deserialize_frames")]
     pub frames: Vec<FrameData>,
     pub meta: MetaData,
+    /// The animation config from the sidecar `.anim.ron` file next to the
+    /// spritesheet, if there is one. It's not part of the aseprite data, but
+    /// is read by the asset loader and applied when a spritesheet is created
+    #[serde(skip)]
+    #[reflect(ignore)]
+    pub anim_config: Option<AnimConfig>,
+    /// The settings that the data was loaded with by the asset loader, which
+    /// are applied when a spritesheet is created from it, except for the
+    /// anchor which is specified separately
+    #[serde(skip)]
+    #[reflect(ignore)]
+    pub loader_settings: Option<SpritesheetLoaderSettings>,
 }
 
 /// A container to hold some data about individual frames from the sprite sheet
 #[derive(Deserialize, Reflect, Debug)]
 // #[uuid = "d49c70a1-177b-44ff-b427-d3929c928667"]
 pub struct FrameData {
+    /// the name of the frame, in the "Hash" layout this is the key that the
+    /// frame is stored under
+    #[serde(default)]
     pub filename: String,
     pub frame: RectData,
     pub rotated: bool,
@@ -27,13 +50,22 @@ pub struct FrameData {
 }
 
 /// A container to hold information about frame tags defined in aseprite
-#[derive(Deserialize, Reflect, Debug)]
+#[derive(Deserialize, Reflect, Clone, Debug)]
 // #[uuid = "f88c0866-6ed2-4b45-a6b6-7dcbe8c53f21"]
 pub struct FrameTagData {
     pub name: String,
     pub from: usize,
     pub to: usize,
     pub direction: String,
+    /// the color of the tag in aseprite
+    #[serde(default)]
+    pub color: Option<String>,
+    /// the user data text attached to the tag, if any
+    #[serde(default)]
+    pub data: Option<String>,
+    /// how many times the tag plays, if it doesn't play forever
+    #[serde(default)]
+    pub repeat: Option<String>,
 }
 
 /// A container to hold spritesheet metadata
@@ -48,6 +80,63 @@ pub struct MetaData {
     pub scale: String,
     #[serde(rename = "frameTags")]
     pub frame_tags: Vec<FrameTagData>,
+    /// the slices defined in aseprite, only exported if the "Slices" option
+    /// is enabled
+    #[serde(default)]
+    pub slices: Vec<SliceData>,
+    /// the layers of the sprite, only exported if the "Layers" option is
+    /// enabled
+    #[serde(default)]
+    pub layers: Vec<LayerData>,
+}
+
+/// A container to hold information about a layer defined in aseprite
+#[derive(Deserialize, Reflect, Clone, Debug)]
+pub struct LayerData {
+    pub name: String,
+    /// the cels on the layer that have user data attached to them
+    #[serde(default)]
+    pub cels: Vec<CelData>,
+}
+
+/// A container to hold the user data attached to a cel in aseprite
+#[derive(Deserialize, Reflect, Clone, Debug)]
+pub struct CelData {
+    /// the index of the frame that the cel is on
+    pub frame: usize,
+    /// the user data color of the cel, if any
+    #[serde(default)]
+    pub color: Option<String>,
+    /// the user data text of the cel, if any
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+/// A container to hold information about a slice defined in aseprite
+#[derive(Deserialize, Reflect, Clone, Debug)]
+pub struct SliceData {
+    pub name: String,
+    pub color: String,
+    /// the user data text of the slice, if any
+    #[serde(default)]
+    pub data: Option<String>,
+    /// the shape of the slice starting at each keyframe
+    pub keys: Vec<SliceKeyData>,
+}
+
+/// A container to hold the shape of a slice starting at a specific frame
+#[derive(Deserialize, Reflect, Clone, Copy, Debug)]
+pub struct SliceKeyData {
+    /// the frame that the key starts at
+    pub frame: usize,
+    /// the bounds of the slice in source frame pixel coordinates
+    pub bounds: RectData,
+    /// the 9-patch center of the slice, relative to its bounds
+    #[serde(default)]
+    pub center: Option<RectData>,
+    /// the pivot point of the slice, relative to its bounds
+    #[serde(default)]
+    pub pivot: Option<PointData>,
 }
 
 /// A container to hold size data objects used to specify 2d sizes output
@@ -59,6 +148,14 @@ pub struct SizeData {
     pub h: u16,
 }
 
+/// A container to hold point data objects used to specify 2d positions output
+/// from aseprite
+#[derive(Deserialize, Reflect, Clone, Copy, Debug)]
+pub struct PointData {
+    pub x: i32,
+    pub y: i32,
+}
+
 /// A container to hold rect data objects used to specify 2d rectangles output
 /// from aseprite
 #[derive(Deserialize, Reflect, Clone, Copy, Debug)]
@@ -82,6 +179,16 @@ impl From<SizeData> for Vec2 {
     }
 }
 
+impl From<PointData> for Vec2 {
+    /// Create a vec2 from deserialized aseprite point data
+    fn from(value: PointData) -> Self {
+        Vec2 {
+            x: value.x as f32,
+            y: value.y as f32,
+        }
+    }
+}
+
 impl From<RectData> for Rect {
     /// Create a rect from deserialized aseprite rect data
     fn from(value: RectData) -> Self {
@@ -97,3 +204,100 @@ impl From<RectData> for Rect {
         }
     }
 }
+
+// Deserialization: ------------------------------------------------------------
+
+/// Deserialize the frames of the spritesheet from either a json array of
+/// frames, or a json object of frames keyed by their filename
+fn deserialize_frames<'de, D>(deserializer: D) -> Result<Vec<FrameData>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(FramesVisitor)
+}
+
+struct FramesVisitor;
+
+impl<'de> Visitor<'de> for FramesVisitor {
+    type Value = Vec<FrameData>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of frames or a map of frames keyed by filename")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut frames = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(frame) = seq.next_element::<FrameData>()? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // entries are visited in the order they appear in the file, so the
+        // frame indices still line up with the frame tag ranges
+        let mut frames = Vec::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some((filename, mut frame)) = map.next_entry::<String, FrameData>()? {
+            frame.filename = filename;
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_layout_keeps_file_order() {
+        // the keys are out of alphabetical order, so the frames would be
+        // reordered if the map wasn't read in the order of the file
+        let json = br#"{
+            "frames": {
+                "walk 0": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 }, "sourceSize": { "w": 8, "h": 8 }, "duration": 100 },
+                "walk 1": { "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 }, "sourceSize": { "w": 8, "h": 8 }, "duration": 100 },
+                "idle 0": { "frame": { "x": 16, "y": 0, "w": 8, "h": 8 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 }, "sourceSize": { "w": 8, "h": 8 }, "duration": 200 },
+                "attack 0": { "frame": { "x": 24, "y": 0, "w": 8, "h": 8 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 }, "sourceSize": { "w": 8, "h": 8 }, "duration": 50 }
+            },
+            "meta": {
+                "app": "test",
+                "version": "1",
+                "image": "test.png",
+                "format": "RGBA8888",
+                "size": { "w": 32, "h": 8 },
+                "scale": "1",
+                "frameTags": [
+                    { "name": "walk", "from": 0, "to": 1, "direction": "forward" },
+                    { "name": "idle", "from": 2, "to": 2, "direction": "forward" },
+                    { "name": "attack", "from": 3, "to": 3, "direction": "forward" }
+                ]
+            }
+        }"#;
+        let data: SpritesheetData = serde_json::from_slice(json).unwrap();
+
+        let names: Vec<&str> = data
+            .frames
+            .iter()
+            .map(|frame| frame.filename.as_str())
+            .collect();
+        assert_eq!(names, ["walk 0", "walk 1", "idle 0", "attack 0"]);
+        let xs: Vec<u16> = data.frames.iter().map(|frame| frame.frame.x).collect();
+        assert_eq!(xs, [0, 8, 16, 24]);
+
+        // each tag range covers the frames that were exported for it
+        for tag in &data.meta.frame_tags {
+            for frame in &data.frames[tag.from..=tag.to] {
+                assert!(frame.filename.starts_with(&tag.name));
+            }
+        }
+    }
+}
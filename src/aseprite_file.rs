@@ -0,0 +1,953 @@
+use crate::aseprite_data::*;
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use flate2::read::ZlibDecoder;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+// Constants: ------------------------------------------------------------------
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_USER_DATA: u16 = 0x2020;
+const CHUNK_SLICE: u16 = 0x2022;
+
+const CEL_RAW: u16 = 0;
+const CEL_LINKED: u16 = 1;
+const CEL_COMPRESSED: u16 = 2;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const LAYER_REFERENCE: u16 = 64;
+const LAYER_TYPE_NORMAL: u16 = 0;
+const LAYER_BLEND_NORMAL: u16 = 0;
+
+const HEADER_FLAG_LAYER_OPACITY: u32 = 1;
+
+const USER_DATA_FLAG_TEXT: u32 = 1;
+const USER_DATA_FLAG_COLOR: u32 = 2;
+
+const SLICE_FLAG_NINE_PATCH: u32 = 1;
+const SLICE_FLAG_PIVOT: u32 = 2;
+
+/// the most colors that a palette can have in aseprite
+const MAX_PALETTE_SIZE: usize = 65536;
+
+/// the color that aseprite gives new slices
+const DEFAULT_SLICE_COLOR: &str = "#0000ffff";
+
+// Struct Definitions: ---------------------------------------------------------
+
+/// The parsed contents of a binary `.aseprite` / `.ase` file, with every frame
+/// already composited from the visible layers
+#[derive(Debug, Clone)]
+pub struct AsepriteFile {
+    /// the width of the sprite canvas in pixels
+    pub width: u16,
+    /// the height of the sprite canvas in pixels
+    pub height: u16,
+    /// each frame of the sprite in order
+    pub frames: Vec<AsepriteFrame>,
+    /// the frame tags defined in the sprite
+    pub tags: Vec<FrameTagData>,
+    /// the slices defined in the sprite
+    pub slices: Vec<SliceData>,
+    /// the layers of the sprite, along with any cel user data on them
+    pub layers: Vec<LayerData>,
+}
+
+/// A single composited frame of an aseprite file
+#[derive(Debug, Clone)]
+pub struct AsepriteFrame {
+    /// how long the frame is displayed for, in milliseconds
+    pub duration: u16,
+    /// the RGBA8 pixels of the frame, the size of the sprite canvas
+    pub pixels: Vec<u8>,
+}
+
+struct Layer {
+    name: String,
+    flags: u16,
+    layer_type: u16,
+    child_level: u16,
+    blend_mode: u16,
+    opacity: u8,
+}
+
+struct Cel {
+    layer: usize,
+    x: i16,
+    y: i16,
+    opacity: u8,
+    z_index: i16,
+    content: CelContent,
+}
+
+struct UserData {
+    text: Option<String>,
+    color: Option<String>,
+}
+
+/// What the next user data chunk in the file is attached to
+enum UserDataTarget {
+    None,
+    Cel { frame: usize, layer: usize },
+    Tag(usize),
+    Slice(usize),
+}
+
+enum CelContent {
+    Image {
+        width: u16,
+        height: u16,
+        pixels: Vec<u8>,
+    },
+    Linked(u16),
+}
+
+// Struct Implementations: -----------------------------------------------------
+
+impl AsepriteFile {
+    /// Parse an aseprite file from its raw bytes
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Cursor::new(bytes);
+
+        // header
+        let _file_size = read_u32(&mut reader)?;
+        if read_u16(&mut reader)? != HEADER_MAGIC {
+            return Err(invalid_data("not an aseprite file"));
+        }
+        let frame_count = read_u16(&mut reader)?;
+        let width = read_u16(&mut reader)?;
+        let height = read_u16(&mut reader)?;
+        let color_depth = read_u16(&mut reader)?;
+        let flags = read_u32(&mut reader)?;
+        reader.seek(SeekFrom::Current(2 + 4 + 4))?;
+        let transparent_index = read_u8(&mut reader)?;
+        reader.seek(SeekFrom::Start(128))?;
+        if !matches!(color_depth, 8 | 16 | 32) {
+            return Err(invalid_data("unsupported color depth"));
+        }
+        // every frame is composited into a canvas of its own, so make sure
+        // that they fit in an atlas before allocating them
+        atlas_layout(frame_count as usize, width, height)?;
+
+        let mut layers = Vec::<Layer>::new();
+        let mut palette = Vec::<[u8; 4]>::new();
+        let mut has_new_palette = false;
+        let mut tags = Vec::<FrameTagData>::new();
+        let mut slices = Vec::<SliceData>::new();
+        let mut cel_user_data = Vec::<(usize, CelData)>::new();
+        let mut frame_cels = Vec::<Vec<Cel>>::with_capacity(frame_count as usize);
+        let mut durations = Vec::<u16>::with_capacity(frame_count as usize);
+
+        for frame_index in 0..frame_count as usize {
+            let frame_start = reader.position();
+            let frame_size = read_u32(&mut reader)? as u64;
+            if read_u16(&mut reader)? != FRAME_MAGIC {
+                return Err(invalid_data("invalid frame header"));
+            }
+            let old_chunk_count = read_u16(&mut reader)?;
+            durations.push(read_u16(&mut reader)?);
+            reader.seek(SeekFrom::Current(2))?;
+            let new_chunk_count = read_u32(&mut reader)?;
+            let chunk_count = if new_chunk_count == 0 {
+                old_chunk_count as u32
+            } else {
+                new_chunk_count
+            };
+
+            let mut cels = Vec::new();
+            let mut user_data_target = UserDataTarget::None;
+            for _ in 0..chunk_count {
+                let chunk_start = reader.position();
+                let chunk_size = read_u32(&mut reader)? as u64;
+                let chunk_type = read_u16(&mut reader)?;
+                match chunk_type {
+                    CHUNK_LAYER => {
+                        layers.push(read_layer(&mut reader)?);
+                        user_data_target = UserDataTarget::None;
+                    }
+                    CHUNK_CEL => {
+                        let data_len = chunk_size.saturating_sub(6) as usize;
+                        user_data_target = UserDataTarget::None;
+                        if let Some(cel) = read_cel(&mut reader, data_len, color_depth)? {
+                            user_data_target = UserDataTarget::Cel {
+                                frame: frame_index,
+                                layer: cel.layer,
+                            };
+                            cels.push(cel);
+                        }
+                    }
+                    CHUNK_TAGS => {
+                        tags = read_tags(&mut reader)?;
+                        user_data_target = UserDataTarget::Tag(0);
+                    }
+                    CHUNK_SLICE => {
+                        slices.push(read_slice(&mut reader)?);
+                        user_data_target = UserDataTarget::Slice(slices.len() - 1);
+                    }
+                    CHUNK_USER_DATA => {
+                        let user_data = read_user_data(&mut reader)?;
+                        match user_data_target {
+                            UserDataTarget::Cel { frame, layer } => {
+                                cel_user_data.push((
+                                    layer,
+                                    CelData {
+                                        frame,
+                                        color: user_data.color,
+                                        data: user_data.text,
+                                    },
+                                ));
+                                user_data_target = UserDataTarget::None;
+                            }
+                            // each tag has its own user data chunk, in order
+                            UserDataTarget::Tag(index) => {
+                                if let Some(tag) = tags.get_mut(index) {
+                                    tag.color = user_data.color.or(tag.color.take());
+                                    tag.data = user_data.text;
+                                }
+                                user_data_target = UserDataTarget::Tag(index + 1);
+                            }
+                            UserDataTarget::Slice(index) => {
+                                let slice = &mut slices[index];
+                                if let Some(color) = user_data.color {
+                                    slice.color = color;
+                                }
+                                slice.data = user_data.text;
+                                user_data_target = UserDataTarget::None;
+                            }
+                            UserDataTarget::None => {}
+                        }
+                    }
+                    CHUNK_PALETTE => {
+                        read_palette(&mut reader, &mut palette)?;
+                        has_new_palette = true;
+                    }
+                    CHUNK_OLD_PALETTE if !has_new_palette => {
+                        read_old_palette(&mut reader, &mut palette)?;
+                    }
+                    _ => {}
+                }
+                reader.seek(SeekFrom::Start(chunk_start + chunk_size))?;
+            }
+            frame_cels.push(cels);
+            reader.seek(SeekFrom::Start(frame_start + frame_size))?;
+        }
+
+        // composite the visible layers of each frame into rgba pixels
+        let visible = visible_layers(&layers);
+        for (layer, _) in layers
+            .iter()
+            .zip(&visible)
+            .filter(|(layer, visible)| **visible && layer.blend_mode != LAYER_BLEND_NORMAL)
+        {
+            warn!(
+                "Layer '{}' uses a blend mode other than normal, which is not supported, it's \
+                composited as normal",
+                layer.name
+            );
+        }
+        let layer_opacity_valid = flags & HEADER_FLAG_LAYER_OPACITY != 0;
+        let mut frames = Vec::with_capacity(frame_cels.len());
+        for (frame_index, cels) in frame_cels.iter().enumerate() {
+            let mut draw_order: Vec<&Cel> = cels
+                .iter()
+                .filter(|cel| visible.get(cel.layer).copied().unwrap_or(false))
+                .collect();
+            draw_order.sort_by_key(|cel| (cel.layer as i32 + cel.z_index as i32, cel.z_index));
+
+            let mut pixels = vec![0u8; image_len(width, height, 4)?];
+            for cel in draw_order {
+                let layer = &layers[cel.layer];
+                let Some((cel_width, cel_height, cel_pixels)) = resolve_cel_image(cel, &frame_cels)
+                else {
+                    continue;
+                };
+                let layer_opacity = if layer_opacity_valid {
+                    layer.opacity
+                } else {
+                    255
+                };
+                let opacity = mul_un8(cel.opacity, layer_opacity);
+                let is_background = layer.flags & LAYER_BACKGROUND != 0;
+                for cel_y in 0..cel_height as i32 {
+                    let y = cel.y as i32 + cel_y;
+                    if y < 0 || y >= height as i32 {
+                        continue;
+                    }
+                    for cel_x in 0..cel_width as i32 {
+                        let x = cel.x as i32 + cel_x;
+                        if x < 0 || x >= width as i32 {
+                            continue;
+                        }
+                        let src_index = cel_y as usize * cel_width as usize + cel_x as usize;
+                        let src = to_rgba(
+                            cel_pixels,
+                            src_index,
+                            color_depth,
+                            &palette,
+                            transparent_index,
+                            is_background,
+                        );
+                        let dst_index = (y as usize * width as usize + x as usize) * 4;
+                        blend_normal(&mut pixels[dst_index..dst_index + 4], src, opacity);
+                    }
+                }
+            }
+            frames.push(AsepriteFrame {
+                duration: durations[frame_index],
+                pixels,
+            });
+        }
+
+        // gather the cel user data onto the layers, like in the json export
+        let mut layer_data: Vec<LayerData> = layers
+            .iter()
+            .map(|layer| LayerData {
+                name: layer.name.clone(),
+                cels: Vec::new(),
+            })
+            .collect();
+        for (layer, cel) in cel_user_data {
+            if let Some(data) = layer_data.get_mut(layer) {
+                data.cels.push(cel);
+            }
+        }
+
+        Ok(AsepriteFile {
+            width,
+            height,
+            frames,
+            tags,
+            slices,
+            layers: layer_data,
+        })
+    }
+
+    /// Pack every frame into a single atlas image, laid out in a grid, and
+    /// generate the [`SpritesheetData`] that describes it, in the same format
+    /// that aseprite exports as json. The image is referenced by the
+    /// specified image path in the data. Fails if the atlas would be too big
+    /// for the frame positions to fit in the data
+    pub fn to_spritesheet(&self, image_path: String) -> io::Result<(SpritesheetData, Image)> {
+        // the frame positions are within the atlas, so they fit in the data
+        // as long as the atlas size does
+        let (columns, atlas_width, atlas_height) =
+            atlas_layout(self.frames.len(), self.width, self.height)?;
+        let frame_width = self.width as usize;
+        let frame_height = self.height as usize;
+        let (atlas_width, atlas_height) = (atlas_width as usize, atlas_height as usize);
+
+        let mut atlas_pixels = vec![0u8; atlas_width * atlas_height * 4];
+        let mut frames = Vec::with_capacity(self.frames.len());
+        for (i, frame) in self.frames.iter().enumerate() {
+            let atlas_x = (i % columns) * frame_width;
+            let atlas_y = (i / columns) * frame_height;
+            for row in 0..frame_height {
+                let src = row * frame_width * 4;
+                let dst = ((atlas_y + row) * atlas_width + atlas_x) * 4;
+                let src_row = frame
+                    .pixels
+                    .get(src..src + frame_width * 4)
+                    .ok_or_else(|| invalid_data("frame is smaller than the sprite"))?;
+                atlas_pixels[dst..dst + frame_width * 4].copy_from_slice(src_row);
+            }
+            frames.push(FrameData {
+                filename: i.to_string(),
+                frame: RectData {
+                    x: atlas_x as u16,
+                    y: atlas_y as u16,
+                    w: self.width,
+                    h: self.height,
+                },
+                rotated: false,
+                trimmed: false,
+                sprite_source_size: RectData {
+                    x: 0,
+                    y: 0,
+                    w: self.width,
+                    h: self.height,
+                },
+                source_size: SizeData {
+                    w: self.width,
+                    h: self.height,
+                },
+                duration: frame.duration as u32,
+            });
+        }
+
+        let size = SizeData {
+            w: atlas_width as u16,
+            h: atlas_height as u16,
+        };
+        let data = SpritesheetData {
+            frames,
+            meta: MetaData {
+                app: "https://www.aseprite.org/".to_string(),
+                version: String::new(),
+                image: image_path,
+                format: "RGBA8888".to_string(),
+                size,
+                scale: "1".to_string(),
+                frame_tags: self.tags.clone(),
+                slices: self.slices.clone(),
+                layers: self.layers.clone(),
+            },
+            anim_config: None,
+            loader_settings: None,
+        };
+        let image = Image::new(
+            Extent3d {
+                width: atlas_width as u32,
+                height: atlas_height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            atlas_pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        Ok((data, image))
+    }
+}
+
+// Chunk Parsing: --------------------------------------------------------------
+
+fn read_layer(reader: &mut Cursor<&[u8]>) -> io::Result<Layer> {
+    let flags = read_u16(reader)?;
+    let layer_type = read_u16(reader)?;
+    let child_level = read_u16(reader)?;
+    reader.seek(SeekFrom::Current(2 + 2))?;
+    let blend_mode = read_u16(reader)?;
+    let opacity = read_u8(reader)?;
+    reader.seek(SeekFrom::Current(3))?;
+    let name = read_string(reader)?;
+    Ok(Layer {
+        name,
+        flags,
+        layer_type,
+        child_level,
+        blend_mode,
+        opacity,
+    })
+}
+
+fn read_cel(
+    reader: &mut Cursor<&[u8]>,
+    data_len: usize,
+    color_depth: u16,
+) -> io::Result<Option<Cel>> {
+    let data_start = reader.position();
+    let layer = read_u16(reader)? as usize;
+    let x = read_i16(reader)?;
+    let y = read_i16(reader)?;
+    let opacity = read_u8(reader)?;
+    let cel_type = read_u16(reader)?;
+    let z_index = read_i16(reader)?;
+    reader.seek(SeekFrom::Current(5))?;
+    let bytes_per_pixel = color_depth as usize / 8;
+
+    let content = match cel_type {
+        CEL_RAW => {
+            let width = read_u16(reader)?;
+            let height = read_u16(reader)?;
+            let pixels_len = image_len(width, height, bytes_per_pixel)?;
+            let header_len = (reader.position() - data_start) as usize;
+            if pixels_len > data_len.saturating_sub(header_len) {
+                return Err(invalid_data("cel data out of bounds"));
+            }
+            let mut pixels = vec![0u8; pixels_len];
+            reader.read_exact(&mut pixels)?;
+            CelContent::Image {
+                width,
+                height,
+                pixels,
+            }
+        }
+        CEL_LINKED => CelContent::Linked(read_u16(reader)?),
+        CEL_COMPRESSED => {
+            let width = read_u16(reader)?;
+            let height = read_u16(reader)?;
+            let header_len = (reader.position() - data_start) as usize;
+            let compressed_len = data_len.saturating_sub(header_len);
+            let start = reader.position() as usize;
+            let compressed = reader
+                .get_ref()
+                .get(start..start + compressed_len)
+                .ok_or_else(|| invalid_data("cel data out of bounds"))?;
+            // the buffer only grows as the data is decompressed, so a cel size
+            // that the data doesn't back up can't cause a huge allocation
+            let pixels_len = image_len(width, height, bytes_per_pixel)?;
+            let mut pixels = Vec::new();
+            ZlibDecoder::new(compressed)
+                .take(pixels_len as u64)
+                .read_to_end(&mut pixels)?;
+            if pixels.len() != pixels_len {
+                return Err(invalid_data("cel data is shorter than the cel"));
+            }
+            CelContent::Image {
+                width,
+                height,
+                pixels,
+            }
+        }
+        // tilemap cels are not supported
+        _ => return Ok(None),
+    };
+
+    Ok(Some(Cel {
+        layer,
+        x,
+        y,
+        opacity,
+        z_index,
+        content,
+    }))
+}
+
+fn read_tags(reader: &mut Cursor<&[u8]>) -> io::Result<Vec<FrameTagData>> {
+    let tag_count = read_u16(reader)?;
+    reader.seek(SeekFrom::Current(8))?;
+    let mut tags = Vec::with_capacity(tag_count as usize);
+    for _ in 0..tag_count {
+        let from = read_u16(reader)? as usize;
+        let to = read_u16(reader)? as usize;
+        let direction = match read_u8(reader)? {
+            1 => "reverse",
+            2 => "pingpong",
+            3 => "pingpong_reverse",
+            _ => "forward",
+        };
+        let repeat = read_u16(reader)?;
+        reader.seek(SeekFrom::Current(6))?;
+        let mut rgb = [0u8; 3];
+        reader.read_exact(&mut rgb)?;
+        reader.seek(SeekFrom::Current(1))?;
+        let name = read_string(reader)?;
+        tags.push(FrameTagData {
+            name,
+            from,
+            to,
+            direction: direction.to_string(),
+            color: Some(hex_color([rgb[0], rgb[1], rgb[2], 255])),
+            data: None,
+            repeat: (repeat > 0).then(|| repeat.to_string()),
+        });
+    }
+    Ok(tags)
+}
+
+fn read_user_data(reader: &mut Cursor<&[u8]>) -> io::Result<UserData> {
+    let flags = read_u32(reader)?;
+    let text = if flags & USER_DATA_FLAG_TEXT != 0 {
+        Some(read_string(reader)?)
+    } else {
+        None
+    };
+    let color = if flags & USER_DATA_FLAG_COLOR != 0 {
+        let mut rgba = [0u8; 4];
+        reader.read_exact(&mut rgba)?;
+        Some(hex_color(rgba))
+    } else {
+        None
+    };
+    // any user data properties are not needed and are skipped along with
+    // the rest of the chunk
+    Ok(UserData { text, color })
+}
+
+fn read_slice(reader: &mut Cursor<&[u8]>) -> io::Result<SliceData> {
+    let key_count = read_u32(reader)?;
+    let flags = read_u32(reader)?;
+    reader.seek(SeekFrom::Current(4))?;
+    let name = read_string(reader)?;
+    let mut keys = Vec::new();
+    for _ in 0..key_count {
+        let frame = read_u32(reader)? as usize;
+        let bounds = read_rect(reader)?;
+        let center = if flags & SLICE_FLAG_NINE_PATCH != 0 {
+            Some(read_rect(reader)?)
+        } else {
+            None
+        };
+        let pivot = if flags & SLICE_FLAG_PIVOT != 0 {
+            Some(PointData {
+                x: read_i32(reader)?,
+                y: read_i32(reader)?,
+            })
+        } else {
+            None
+        };
+        keys.push(SliceKeyData {
+            frame,
+            bounds,
+            center,
+            pivot,
+        });
+    }
+    Ok(SliceData {
+        name,
+        color: DEFAULT_SLICE_COLOR.to_string(),
+        data: None,
+        keys,
+    })
+}
+
+fn read_palette(reader: &mut Cursor<&[u8]>, palette: &mut Vec<[u8; 4]>) -> io::Result<()> {
+    let size = read_u32(reader)? as usize;
+    let first = read_u32(reader)? as usize;
+    let last = read_u32(reader)? as usize;
+    reader.seek(SeekFrom::Current(8))?;
+    if first > last || last >= MAX_PALETTE_SIZE {
+        return Err(invalid_data("invalid palette range"));
+    }
+    palette.resize(size.min(MAX_PALETTE_SIZE).max(last + 1), [0; 4]);
+    for entry in palette.iter_mut().take(last + 1).skip(first) {
+        let flags = read_u16(reader)?;
+        reader.read_exact(entry)?;
+        if flags & 1 != 0 {
+            read_string(reader)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_old_palette(reader: &mut Cursor<&[u8]>, palette: &mut Vec<[u8; 4]>) -> io::Result<()> {
+    let packet_count = read_u16(reader)?;
+    let mut index = 0;
+    for _ in 0..packet_count {
+        index += read_u8(reader)? as usize;
+        let color_count = match read_u8(reader)? {
+            0 => 256,
+            count => count as usize,
+        };
+        for _ in 0..color_count {
+            let mut rgb = [0u8; 3];
+            reader.read_exact(&mut rgb)?;
+            if palette.len() <= index {
+                palette.resize(index + 1, [0; 4]);
+            }
+            palette[index] = [rgb[0], rgb[1], rgb[2], 255];
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+// Compositing: ----------------------------------------------------------------
+
+/// Determine which layers should be drawn, a layer is only drawn if it and all
+/// of the groups that it is nested in are visible
+fn visible_layers(layers: &[Layer]) -> Vec<bool> {
+    let mut visible = Vec::with_capacity(layers.len());
+    // the visibility of the most recent layer at each child level
+    let mut parent_visible = Vec::<bool>::new();
+    for layer in layers {
+        let level = layer.child_level as usize;
+        parent_visible.truncate(level);
+        let parent = parent_visible.last().copied().unwrap_or(true);
+        let is_visible =
+            parent && layer.flags & LAYER_VISIBLE != 0 && layer.flags & LAYER_REFERENCE == 0;
+        parent_visible.resize(level, parent);
+        parent_visible.push(is_visible);
+        visible.push(is_visible && layer.layer_type == LAYER_TYPE_NORMAL);
+    }
+    visible
+}
+
+fn resolve_cel_image<'a>(cel: &'a Cel, frame_cels: &'a [Vec<Cel>]) -> Option<(u16, u16, &'a [u8])> {
+    match &cel.content {
+        CelContent::Image {
+            width,
+            height,
+            pixels,
+        } => Some((*width, *height, pixels)),
+        CelContent::Linked(frame) => {
+            let linked = frame_cels
+                .get(*frame as usize)?
+                .iter()
+                .find(|other| other.layer == cel.layer)?;
+            match &linked.content {
+                CelContent::Image {
+                    width,
+                    height,
+                    pixels,
+                } => Some((*width, *height, pixels)),
+                CelContent::Linked(_) => None,
+            }
+        }
+    }
+}
+
+fn to_rgba(
+    pixels: &[u8],
+    index: usize,
+    color_depth: u16,
+    palette: &[[u8; 4]],
+    transparent_index: u8,
+    is_background: bool,
+) -> [u8; 4] {
+    match color_depth {
+        32 => {
+            let i = index * 4;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        }
+        16 => {
+            let i = index * 2;
+            let value = pixels[i];
+            [value, value, value, pixels[i + 1]]
+        }
+        _ => {
+            let palette_index = pixels[index];
+            if palette_index == transparent_index && !is_background {
+                return [0; 4];
+            }
+            palette
+                .get(palette_index as usize)
+                .copied()
+                .unwrap_or([0; 4])
+        }
+    }
+}
+
+/// Composite the source color over the destination pixel with aseprite's
+/// "normal" blend mode
+fn blend_normal(dst: &mut [u8], src: [u8; 4], opacity: u8) {
+    let src_alpha = mul_un8(src[3], opacity) as u32;
+    if src_alpha == 0 {
+        return;
+    }
+    let dst_alpha = dst[3] as u32;
+    let out_alpha = src_alpha + dst_alpha * (255 - src_alpha) / 255;
+    for channel in 0..3 {
+        let src_c = src[channel] as u32 * src_alpha;
+        let dst_c = dst[channel] as u32 * dst_alpha * (255 - src_alpha) / 255;
+        dst[channel] = ((src_c + dst_c) / out_alpha) as u8;
+    }
+    dst[3] = out_alpha as u8;
+}
+
+fn mul_un8(a: u8, b: u8) -> u8 {
+    ((a as u32 * b as u32 + 127) / 255) as u8
+}
+
+// Utility: --------------------------------------------------------------------
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// The amount of bytes in an image of the specified size
+fn image_len(width: u16, height: u16, bytes_per_pixel: usize) -> io::Result<usize> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|len| len.checked_mul(bytes_per_pixel))
+        .ok_or_else(|| invalid_data("image is too large"))
+}
+
+/// The amount of columns in the grid that the specified amount of frames are
+/// laid out in within an atlas, and the width and height of the atlas. Fails
+/// if the atlas is too big for the frame positions to fit in the data
+fn atlas_layout(frame_count: usize, width: u16, height: u16) -> io::Result<(usize, u16, u16)> {
+    let frame_count = frame_count.max(1);
+    let columns = (frame_count as f32).sqrt().ceil() as usize;
+    let rows = frame_count.div_ceil(columns);
+    let atlas_size = |count: usize, frame_size: u16| {
+        count
+            .checked_mul(frame_size as usize)
+            .and_then(|size| u16::try_from(size).ok())
+            .ok_or_else(|| invalid_data("too many frames to fit in an atlas"))
+    };
+    let atlas_width = atlas_size(columns, width)?;
+    let atlas_height = atlas_size(rows, height)?;
+    Ok((columns, atlas_width, atlas_height))
+}
+
+/// Format a color the same way that aseprite does in its json export
+fn hex_color(rgba: [u8; 4]) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        rgba[0], rgba[1], rgba[2], rgba[3]
+    )
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_i16(reader: &mut impl Read) -> io::Result<i16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(i16::from_le_bytes(buf))
+}
+
+fn read_i32(reader: &mut impl Read) -> io::Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+/// Read a rect stored as a signed position and unsigned size, parts of the
+/// rect that lie at negative coordinates are clamped to zero
+fn read_rect(reader: &mut impl Read) -> io::Result<RectData> {
+    let x = read_i32(reader)?;
+    let y = read_i32(reader)?;
+    let w = read_u32(reader)? as i64;
+    let h = read_u32(reader)? as i64;
+    let clamp = |value: i64| value.clamp(0, u16::MAX as i64) as u16;
+    let (min_x, min_y) = (clamp(x as i64), clamp(y as i64));
+    Ok(RectData {
+        x: min_x,
+        y: min_y,
+        w: clamp(x as i64 + w) - min_x,
+        h: clamp(y as i64 + h) - min_y,
+    })
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let len = read_u16(reader)?;
+    let mut buf = vec![0u8; len as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_matches_json_export() {
+        let file = AsepriteFile::parse(include_bytes!("../assets/witch.aseprite")).unwrap();
+        let (data, _) = file
+            .to_spritesheet("witch.aseprite#image".to_string())
+            .unwrap();
+        let json: SpritesheetData =
+            serde_json::from_slice(include_bytes!("../assets/witch.sprite.json")).unwrap();
+
+        assert_eq!(data.frames.len(), json.frames.len());
+        for (frame, json_frame) in data.frames.iter().zip(&json.frames) {
+            assert_eq!(frame.duration, json_frame.duration);
+            assert_eq!(frame.source_size.w, json_frame.source_size.w);
+            assert_eq!(frame.source_size.h, json_frame.source_size.h);
+        }
+
+        assert_eq!(data.meta.frame_tags.len(), json.meta.frame_tags.len());
+        for (tag, json_tag) in data.meta.frame_tags.iter().zip(&json.meta.frame_tags) {
+            assert_eq!(tag.name, json_tag.name);
+            assert_eq!(tag.from, json_tag.from);
+            assert_eq!(tag.to, json_tag.to);
+            assert_eq!(tag.direction, json_tag.direction);
+        }
+    }
+
+    #[test]
+    fn frames_match_exported_image() {
+        let file = AsepriteFile::parse(include_bytes!("../assets/witch.aseprite")).unwrap();
+        let json: SpritesheetData =
+            serde_json::from_slice(include_bytes!("../assets/witch.sprite.json")).unwrap();
+        let png = image::load_from_memory(include_bytes!("../assets/witch.png"))
+            .unwrap()
+            .to_rgba8();
+
+        // the exported image has the frames trimmed and packed, so each frame
+        // is compared to its rect in the image, and must be empty outside of it
+        assert_eq!(file.frames.len(), json.frames.len());
+        for (frame, json_frame) in file.frames.iter().zip(&json.frames) {
+            let rect = json_frame.frame;
+            let offset = json_frame.sprite_source_size;
+            for y in 0..file.height as u32 {
+                for x in 0..file.width as u32 {
+                    let i = (y as usize * file.width as usize + x as usize) * 4;
+                    let pixel = &frame.pixels[i..i + 4];
+                    let (trimmed_x, trimmed_y) =
+                        (x as i32 - offset.x as i32, y as i32 - offset.y as i32);
+                    let inside = (0..rect.w as i32).contains(&trimmed_x)
+                        && (0..rect.h as i32).contains(&trimmed_y);
+                    let expected = if inside {
+                        png.get_pixel(
+                            rect.x as u32 + trimmed_x as u32,
+                            rect.y as u32 + trimmed_y as u32,
+                        )
+                        .0
+                    } else {
+                        [0; 4]
+                    };
+                    // the color of fully transparent pixels doesn't matter
+                    let expected = if expected[3] == 0 { [0; 4] } else { expected };
+                    assert_eq!(
+                        pixel, expected,
+                        "frame '{}' at {}, {}",
+                        json_frame.filename, x, y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_cel_is_an_error() {
+        let mut bytes = include_bytes!("../assets/witch.aseprite").to_vec();
+        // make the first cel claim to be as large as possible
+        let cel = find_chunk(&bytes, CHUNK_CEL).unwrap();
+        assert_eq!(
+            u16::from_le_bytes([bytes[cel + 13], bytes[cel + 14]]),
+            CEL_COMPRESSED
+        );
+        bytes[cel + 22..cel + 26].copy_from_slice(&[0xFF; 4]);
+        assert!(AsepriteFile::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn oversized_canvas_is_an_error() {
+        // a header with a huge canvas and many frames, without any frame data
+        let mut bytes = vec![0u8; 128];
+        bytes[4..6].copy_from_slice(&HEADER_MAGIC.to_le_bytes());
+        bytes[6..8].copy_from_slice(&u16::MAX.to_le_bytes());
+        bytes[8..10].copy_from_slice(&u16::MAX.to_le_bytes());
+        bytes[10..12].copy_from_slice(&u16::MAX.to_le_bytes());
+        bytes[12..14].copy_from_slice(&32u16.to_le_bytes());
+        let err = AsepriteFile::parse(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// The position of the first chunk of the specified type in the first
+    /// frame of the file
+    fn find_chunk(bytes: &[u8], chunk_type: u16) -> Option<usize> {
+        let mut pos = 128 + 16;
+        while pos + 6 <= bytes.len() {
+            let size = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+            if u16::from_le_bytes([bytes[pos + 4], bytes[pos + 5]]) == chunk_type {
+                return Some(pos);
+            }
+            pos += size.max(6);
+        }
+        None
+    }
+}
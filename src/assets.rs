@@ -1,35 +1,572 @@
-use crate::aseprite_data::SpritesheetData;
-use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt};
+use crate::{
+    anim_graph::AnimGraph,
+    aseprite_data::SpritesheetData,
+    aseprite_file::AsepriteFile,
+    sprite::{
+        AnchorDef, AnimConfig, AnimEndAction, AnimEndActionDef, AnimHandle, AnimSettings,
+        Spritesheet,
+    },
+};
+use bevy::{
+    asset::{
+        io::{AssetReaderError, Reader},
+        AssetLoader, AssetPath, AsyncReadExt, LoadContext, ReadAssetBytesError,
+    },
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+    utils::HashMap,
+};
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
 use serde_json::from_slice;
+use thiserror::Error;
 
 // Struct Definitions: ---------------------------------------------------------
 
+/// An error that can occur when loading a spritesheet asset. Failed loads are
+/// reported through bevy's asset load state instead of panicking
+#[derive(Error, Debug)]
+pub enum SpritesheetLoadError {
+    /// The spritesheet file could not be read
+    #[error("could not read spritesheet: {0}")]
+    Io(#[from] std::io::Error),
+    /// The spritesheet json is malformed or does not match the aseprite format
+    #[error("invalid spritesheet json at line {line}, column {column}: {message}")]
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The spritesheet data is well formed but is not internally consistent,
+    /// for example a frame tag refers to frames that don't exist
+    #[error("invalid spritesheet data: {0}")]
+    Schema(String),
+    /// The sidecar animation config file next to the spritesheet could not be
+    /// read or is not valid
+    #[error("invalid animation config '{path}': {message}")]
+    AnimConfig { path: String, message: String },
+}
+
+/// An error that can occur when loading an [`AnimGraph`] asset
+#[derive(Error, Debug)]
+pub enum AnimGraphLoadError {
+    /// The animation graph file could not be read
+    #[error("could not read animation graph: {0}")]
+    Io(#[from] std::io::Error),
+    /// The animation graph is not valid RON or does not match the graph format
+    #[error("invalid animation graph: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+/// Settings for loading a [`Spritesheet`] asset, or the [`SpritesheetData`]
+/// that [`crate::core::load_spritesheet`] generates a spritesheet from, which
+/// can be specified with [`AssetServer::load_with_settings`] or in the asset's
+/// `.meta` file. The `.meta` file has to name the loader that the asset is
+/// loaded with, `bevy_asepritesheet::assets::SpritesheetLoader` for
+/// spritesheets loaded with [`AssetServer::load`], or
+/// `bevy_asepritesheet::assets::SpritesheetAssetLoader` for json and
+/// `bevy_asepritesheet::assets::AsepriteAssetLoader` for aseprite files loaded
+/// with [`crate::core::load_spritesheet`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SpritesheetLoaderSettings {
+    /// The anchor of each frame, relative to the untrimmed frame. Spritesheets
+    /// generated by [`crate::core::load_spritesheet`] use the anchor that's
+    /// passed to it instead
+    pub anchor: AnchorDef,
+    /// The end action of every animation that doesn't override it, frame tags
+    /// with a repeat count in aseprite keep [`AnimEndAction::LoopCount`]
+    pub end_action: AnimEndActionDef,
+    /// The time scale of every animation that doesn't override it
+    pub time_scale: f32,
+    /// Settings for specific animations, by animation name
+    pub anims: HashMap<String, AnimSettings>,
+    /// The sampler used for the spritesheet image, [`ImageSampler::Default`]
+    /// uses the sampler set in bevy's [`ImagePlugin`]
+    pub sampler: ImageSampler,
+}
+
 pub(crate) struct SpritesheetAssetLoader {
     pub extensions: Vec<&'static str>,
 }
 
+/// Loads binary `.aseprite` / `.ase` files directly, compositing the visible
+/// layers of each frame into an atlas image. The image is added as the
+/// labeled sub-asset `"image"`, which the generated [`SpritesheetData`] refers
+/// to. Layer blend modes are not supported, every layer is composited with the
+/// normal blend mode and a warning is logged for layers that use another one
+#[derive(Default)]
+pub(crate) struct AsepriteAssetLoader;
+
+/// Loads [`Spritesheet`] assets directly from json spritesheets or aseprite
+/// files, so that `asset_server.load::<Spritesheet>(path)` can be used. The
+/// image is loaded as a dependency of the spritesheet, and the
+/// [`TextureAtlasLayout`] is added as the labeled sub-asset `"atlas"`
+pub(crate) struct SpritesheetLoader {
+    pub extensions: Vec<&'static str>,
+}
+
+/// Loads `.animgraph.ron` files into [`AnimGraph`] assets
+#[derive(Default)]
+pub(crate) struct AnimGraphAssetLoader;
+
 // Struct Implementations: -----------------------------------------------------
 
+impl Default for SpritesheetLoaderSettings {
+    fn default() -> Self {
+        Self {
+            anchor: AnchorDef::Center,
+            end_action: AnimEndActionDef::Loop,
+            time_scale: 1.0,
+            anims: HashMap::default(),
+            sampler: ImageSampler::Default,
+        }
+    }
+}
+
+impl SpritesheetLoaderSettings {
+    /// Apply the default end action and time scale to every animation in the
+    /// specified spritesheet, followed by the settings for specific animations.
+    /// The default end action isn't applied to frame tags with a repeat count
+    pub fn apply_to_sheet(&self, sheet: &mut Spritesheet) {
+        self.apply_to_sheet_with_config(sheet, None);
+    }
+
+    /// Apply the settings to the specified spritesheet, the defaults are not
+    /// applied to animation settings that were specified in the sidecar
+    /// animation config or to the repeat counts of frame tags, while settings
+    /// for specific animations override them
+    pub(crate) fn apply_to_sheet_with_config(
+        &self,
+        sheet: &mut Spritesheet,
+        config: Option<&AnimConfig>,
+    ) {
+        let anims: Vec<(String, bool)> = (0..sheet.anim_count())
+            .filter_map(|i| sheet.get_anim(&AnimHandle::from_index(i)).ok())
+            .map(|anim| {
+                let repeats = matches!(anim.end_action, AnimEndAction::LoopCount(_));
+                (anim.name.clone(), repeats)
+            })
+            .collect();
+        for (name, repeats) in anims {
+            let configured = config.and_then(|config| config.anims.get(&name));
+            let defaults = AnimSettings {
+                end_action: (!repeats
+                    && configured
+                        .and_then(|settings| settings.end_action.as_ref())
+                        .is_none())
+                .then(|| self.end_action.clone()),
+                time_scale: configured
+                    .and_then(|settings| settings.time_scale)
+                    .is_none()
+                    .then_some(self.time_scale),
+            };
+            sheet.apply_anim_settings(&name, &defaults);
+        }
+        for (name, settings) in &self.anims {
+            if !sheet.apply_anim_settings(name, settings) {
+                warn!(
+                    "Spritesheet loader settings refer to missing animation '{}'",
+                    name
+                );
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for SpritesheetLoadError {
+    fn from(value: serde_json::Error) -> Self {
+        if value.is_io() {
+            return SpritesheetLoadError::Io(value.into());
+        }
+        // the position is stored separately, so strip it from the message
+        let (line, column) = (value.line(), value.column());
+        let message = value.to_string();
+        let position = format!(" at line {} column {}", line, column);
+        SpritesheetLoadError::Json {
+            line,
+            column,
+            message: message
+                .strip_suffix(&position)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+}
+
 impl AssetLoader for SpritesheetAssetLoader {
     type Asset = SpritesheetData;
+    type Settings = SpritesheetLoaderSettings;
+    type Error = SpritesheetLoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let mut data = read_json(&bytes)?;
+            data.anim_config = read_anim_config(load_context).await?;
+            data.loader_settings = Some(settings.clone());
+            Ok(data)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+impl AssetLoader for AsepriteAssetLoader {
+    type Asset = SpritesheetData;
+    type Settings = SpritesheetLoaderSettings;
+    type Error = SpritesheetLoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let (mut data, _) = read_aseprite(&bytes, load_context, &settings.sampler)?;
+            data.anim_config = read_anim_config(load_context).await?;
+            data.loader_settings = Some(settings.clone());
+            Ok(data)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+impl AssetLoader for SpritesheetLoader {
+    type Asset = Spritesheet;
+    type Settings = SpritesheetLoaderSettings;
+    type Error = SpritesheetLoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            // aseprite files contain their own image, while json spritesheets
+            // refer to an image file
+            let is_aseprite = matches!(
+                load_context.path().extension().and_then(|ext| ext.to_str()),
+                Some("aseprite" | "ase")
+            );
+            let (mut data, img_handle) = if is_aseprite {
+                read_aseprite(&bytes, load_context, &settings.sampler)?
+            } else {
+                let data = read_json(&bytes)?;
+                let img_handle = match &settings.sampler {
+                    ImageSampler::Default => load_context.load(&data.meta.image),
+                    sampler => {
+                        let sampler = sampler.clone();
+                        load_context.load_with_settings(
+                            &data.meta.image,
+                            move |img_settings: &mut ImageLoaderSettings| {
+                                img_settings.sampler = sampler.clone();
+                            },
+                        )
+                    }
+                };
+                (data, img_handle)
+            };
+
+            data.anim_config = read_anim_config(load_context).await?;
+            data.loader_settings = Some(settings.clone());
+
+            // build the spritesheet and its atlas from the data
+            let mut sheet = Spritesheet::default();
+            sheet.set_asset_path(load_context.asset_path());
+            sheet.copy_from(&data, &settings.anchor.into());
+            let atlas_handle =
+                load_context.add_labeled_asset("atlas".to_string(), sheet.create_atlas_layout());
+            sheet.set_handles(img_handle, atlas_handle);
+            Ok(sheet)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+impl AssetLoader for AnimGraphAssetLoader {
+    type Asset = AnimGraph;
     type Settings = ();
-    type Error = std::io::Error;
+    type Error = AnimGraphLoadError;
 
     fn load<'a>(
         &'a self,
         reader: &'a mut Reader,
         _settings: &'a Self::Settings,
-        _load_context: &'a mut bevy::asset::LoadContext,
+        load_context: &'a mut bevy::asset::LoadContext,
     ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
-            let asset = from_slice::<SpritesheetData>(&bytes).expect("unable to decode sprite");
-            Ok(asset)
+            let mut graph = ron_options().from_bytes::<AnimGraph>(&bytes)?;
+            graph.spritesheet_handle = graph
+                .spritesheet
+                .as_ref()
+                .map(|path| load_context.load(path));
+            Ok(graph)
         })
     }
 
     fn extensions(&self) -> &[&str] {
-        &self.extensions
+        &["animgraph.ron"]
+    }
+}
+
+// Utility: --------------------------------------------------------------------
+
+/// The options used to parse the RON files of the crate, optional fields can
+/// be written without being wrapped in `Some`
+pub(crate) fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME)
+}
+
+/// Read the sidecar animation config next to the asset being loaded, which has
+/// the same path with the last extension replaced by `.anim.ron`, if it exists.
+/// The config only becomes a dependency of the asset if it exists, since bevy
+/// only tracks the files that a loader read, so a config that's created after
+/// the asset was loaded doesn't hot reload it until the asset itself changes
+async fn read_anim_config(
+    load_context: &mut LoadContext<'_>,
+) -> Result<Option<AnimConfig>, SpritesheetLoadError> {
+    let asset_path = load_context.asset_path();
+    let path = AssetPath::from(asset_path.path().with_extension("anim.ron"))
+        .with_source(asset_path.source().clone_owned());
+    let bytes = match load_context.read_asset_bytes(path.clone()).await {
+        Ok(bytes) => bytes,
+        Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => {
+            return Ok(None);
+        }
+        Err(err) => {
+            return Err(SpritesheetLoadError::AnimConfig {
+                path: path.to_string(),
+                message: err.to_string(),
+            });
+        }
+    };
+    let config = ron_options()
+        .from_bytes::<AnimConfig>(&bytes)
+        .map_err(|err| SpritesheetLoadError::AnimConfig {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+    Ok(Some(config))
+}
+
+/// Parse and validate the spritesheet data from aseprite json
+fn read_json(bytes: &[u8]) -> Result<SpritesheetData, SpritesheetLoadError> {
+    let data = from_slice::<SpritesheetData>(bytes)?;
+    validate(&data)?;
+    Ok(data)
+}
+
+/// Parse a binary aseprite file into spritesheet data, and add its atlas image
+/// with the specified sampler as the labeled sub-asset `"image"`
+fn read_aseprite(
+    bytes: &[u8],
+    load_context: &mut LoadContext,
+    sampler: &ImageSampler,
+) -> Result<(SpritesheetData, Handle<Image>), SpritesheetLoadError> {
+    let file = AsepriteFile::parse(bytes)?;
+    let image_path = load_context
+        .asset_path()
+        .clone()
+        .with_label("image")
+        .to_string();
+    let (data, mut image) = file.to_spritesheet(image_path)?;
+    validate(&data)?;
+    image.sampler = sampler.clone();
+    let img_handle = load_context.add_labeled_asset("image".to_string(), image);
+    Ok((data, img_handle))
+}
+
+/// Check that the spritesheet data is internally consistent, so that a
+/// spritesheet can safely be generated from it
+fn validate(data: &SpritesheetData) -> Result<(), SpritesheetLoadError> {
+    let frame_count = data.frames.len();
+    for tag in &data.meta.frame_tags {
+        if tag.from > tag.to {
+            return Err(SpritesheetLoadError::Schema(format!(
+                "frame tag '{}' starts at frame {} which is after its end frame {}",
+                tag.name, tag.from, tag.to
+            )));
+        }
+        if tag.to >= frame_count {
+            return Err(SpritesheetLoadError::Schema(format!(
+                "frame tag '{}' ends at frame {} but there are only {} frames",
+                tag.name, tag.to, frame_count
+            )));
+        }
+    }
+    let size = data.meta.size;
+    for frame in &data.frames {
+        let rect = frame.frame;
+        if rect.x as u32 + rect.w as u32 > size.w as u32
+            || rect.y as u32 + rect.h as u32 > size.h as u32
+        {
+            return Err(SpritesheetLoadError::Schema(format!(
+                "frame '{}' lies outside of the {}x{} image",
+                frame.filename, size.w, size.h
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::sprite::Anchor;
+
+    /// Spritesheet json with a single 8x8 frame and the specified frame tags
+    fn sheet_json(frame_tags: &str) -> String {
+        format!(
+            r#"{{
+    "frames": [
+        {{
+            "filename": "0",
+            "frame": {{ "x": 0, "y": 0, "w": 8, "h": 8 }},
+            "rotated": false,
+            "trimmed": false,
+            "spriteSourceSize": {{ "x": 0, "y": 0, "w": 8, "h": 8 }},
+            "sourceSize": {{ "w": 8, "h": 8 }},
+            "duration": 100
+        }}
+    ],
+    "meta": {{
+        "app": "test",
+        "version": "1",
+        "image": "test.png",
+        "format": "RGBA8888",
+        "size": {{ "w": 8, "h": 8 }},
+        "scale": "1",
+        "frameTags": [{}]
+    }}
+}}"#,
+            frame_tags
+        )
+    }
+
+    #[test]
+    fn valid_json_loads() {
+        let json = sheet_json(r#"{ "name": "idle", "from": 0, "to": 0, "direction": "forward" }"#);
+        let data = read_json(json.as_bytes()).unwrap();
+        assert_eq!(data.frames.len(), 1);
+    }
+
+    #[test]
+    fn malformed_json_reports_position() {
+        // the closing brace of the first frame tag is missing
+        let json = sheet_json(r#"{ "name": "idle", "from": 0, "to": 0, "direction": "forward" "#);
+        match read_json(json.as_bytes()) {
+            Err(SpritesheetLoadError::Json { line, column, .. }) => {
+                // the error is found at the end of the frame tag list
+                let tags_line = json.lines().nth(line - 1).unwrap();
+                assert!(tags_line.contains("frameTags"));
+                assert_eq!(tags_line.rfind(']'), Some(column - 1));
+            }
+            other => panic!("expected a json error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_field_reports_position() {
+        let json = sheet_json(r#"{ "name": "idle", "from": 0, "direction": "forward" }"#);
+        match read_json(json.as_bytes()) {
+            Err(SpritesheetLoadError::Json {
+                line,
+                column,
+                message,
+            }) => {
+                assert_eq!(line, 20);
+                assert!(column > 0);
+                assert!(message.contains("`to`"), "{}", message);
+                assert!(!message.contains("line"), "{}", message);
+            }
+            other => panic!("expected a json error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn inconsistent_data_is_a_schema_error() {
+        let json = sheet_json(r#"{ "name": "idle", "from": 0, "to": 3, "direction": "forward" }"#);
+        assert!(matches!(
+            read_json(json.as_bytes()),
+            Err(SpritesheetLoadError::Schema(_))
+        ));
+        let json = sheet_json(r#"{ "name": "idle", "from": 1, "to": 0, "direction": "forward" }"#);
+        assert!(matches!(
+            read_json(json.as_bytes()),
+            Err(SpritesheetLoadError::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn loader_settings_and_anim_config_precedence() {
+        let json = sheet_json(
+            r#"{ "name": "idle", "from": 0, "to": 0, "direction": "forward" },
+            { "name": "attack", "from": 0, "to": 0, "direction": "forward" },
+            { "name": "jump", "from": 0, "to": 0, "direction": "forward" }"#,
+        );
+        let mut data = read_json(json.as_bytes()).unwrap();
+        data.anim_config = Some(
+            ron_options()
+                .from_str(
+                    r#"(anims: {
+                        "attack": (end_action: Next("idle")),
+                        "jump": (end_action: Next("idle"), time_scale: 2.0),
+                    })"#,
+                )
+                .unwrap(),
+        );
+        let mut settings = SpritesheetLoaderSettings {
+            end_action: AnimEndActionDef::Pause,
+            time_scale: 0.5,
+            ..default()
+        };
+        settings.anims.insert(
+            "jump".to_string(),
+            AnimSettings {
+                end_action: Some(AnimEndActionDef::Stop),
+                time_scale: None,
+            },
+        );
+        data.loader_settings = Some(settings);
+
+        let mut sheet = Spritesheet::default();
+        sheet.copy_from(&data, &Anchor::Center);
+        let settings_of = |name: &str| {
+            let anim = sheet.get_anim(&sheet.get_anim_handle(name)).unwrap();
+            (anim.end_action, anim.time_scale)
+        };
+        let idle = sheet.get_anim_handle("idle");
+
+        // the defaults only apply where the config doesn't set anything
+        assert_eq!(settings_of("idle"), (AnimEndAction::Pause, 0.5));
+        assert_eq!(settings_of("attack"), (AnimEndAction::Next(idle), 0.5));
+        // while the settings for specific animations override the config
+        assert_eq!(settings_of("jump"), (AnimEndAction::Stop, 2.0));
     }
 }
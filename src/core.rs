@@ -1,13 +1,21 @@
 use crate::{
+    anim_graph::{sync_anim_graphs, AnimGraph},
     animator::{animate_sprites, AnimationSet},
-    assets::SpritesheetAssetLoader,
+    assets::{
+        AnimGraphAssetLoader, AsepriteAssetLoader, SpritesheetAssetLoader, SpritesheetLoader,
+    },
     prelude::*,
-    sprite::{add_needed_atlas_handles, add_needed_img_handles},
+    sprite::{add_needed_atlas_handles, add_needed_img_handles, mark_modified_sheet_entities},
+    state_machine::update_anim_state_machines,
 };
 use bevy::{
-    asset::AssetPath, ecs::schedule::ScheduleLabel, prelude::*, sprite::Anchor,
+    asset::{AssetPath, LoadState},
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    sprite::Anchor,
     utils::intern::Interned,
 };
+use std::sync::Arc;
 
 // Structs: -------------------------------------------------------------------
 
@@ -36,9 +44,96 @@ pub struct SpritesheetLoadedEvent {
     pub data_handle: Handle<SpritesheetData>,
 }
 
+/// Event which gets fired when a spritesheet that was being loaded through a
+/// [`load_spritesheet`] or similar function fails to load, the reason is
+/// logged by the asset server. Listen for it with
+/// [`EventReader<SpritesheetLoadFailedEvent>`]
+#[derive(Event, Debug)]
+pub struct SpritesheetLoadFailedEvent {
+    /// The handle to the spritesheet that will not be generated
+    pub handle: Handle<Spritesheet>,
+    /// The handle to the JSON data asset that failed to load
+    pub data_handle: Handle<SpritesheetData>,
+}
+
+/// Event which gets fired when a spritesheet is loaded whose animations don't match the
+/// [`AnimLabel`]s that were registered for its path with [`AnimLabelsAppExt::verify_anim_labels`].
+/// A warning listing the differences is also logged
+#[derive(Event, Debug)]
+pub struct AnimLabelMismatchEvent {
+    /// The id of the spritesheet that was loaded
+    pub id: AssetId<Spritesheet>,
+    /// The type name of the labels that don't match
+    pub labels: &'static str,
+    /// The differences between the labels and the animations of the spritesheet
+    pub mismatch: AnimLabelMismatch,
+}
+
+/// Adds [`AnimLabelsAppExt::verify_anim_labels`] to the [`App`]
+pub trait AnimLabelsAppExt {
+    /// Check the specified [`AnimLabel`]s against the animations of the spritesheet at the
+    /// specified path every time it's loaded or hot reloaded, whether it's loaded as an asset or
+    /// through [`load_spritesheet`] or a similar function. The labels are checked after the load
+    /// callback has run, and an [`AnimLabelMismatchEvent`] is sent if they don't match
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_asepritesheet::prelude::*;
+    /// # anim_labels! { enum WitchAnim { Idle = "idle" } }
+    /// # fn build(app: &mut App) {
+    /// app.verify_anim_labels::<WitchAnim>("witch.sprite.json");
+    /// # }
+    /// ```
+    fn verify_anim_labels<L: AnimLabel>(
+        &mut self,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self;
+}
+
 #[derive(Component)]
 struct SpriteSheetLoader {
-    on_complete: Option<fn(&mut Spritesheet)>,
+    on_complete: Option<SpritesheetCallback>,
+}
+
+/// A callback that is executed whenever a spritesheet is generated from its
+/// data, both when it's first loaded and when it's hot reloaded
+#[derive(Clone)]
+enum SpritesheetCallback {
+    Sheet(fn(&mut Spritesheet)),
+    World(WorldCallback),
+}
+
+type WorldCallback = Arc<dyn Fn(&mut Spritesheet, &mut World) + Send + Sync>;
+
+/// Keeps track of the spritesheets that were generated through
+/// [`load_spritesheet`] or similar functions, so that they can be rebuilt when
+/// their data is modified, such as when the file is hot reloaded
+#[derive(Resource, Default)]
+struct LoadedSpritesheets(Vec<LoadedSpritesheet>);
+
+struct LoadedSpritesheet {
+    /// strong handle so that the data stays loaded and can be hot reloaded
+    data_handle: Handle<SpritesheetData>,
+    /// weak handle so that the entry is removed once the spritesheet is unused
+    sheet_handle: Handle<Spritesheet>,
+    anchor: Anchor,
+    on_complete: Option<SpritesheetCallback>,
+}
+
+/// The label checks registered with [`AnimLabelsAppExt::verify_anim_labels`]
+#[derive(Resource, Default)]
+struct AnimLabelChecks {
+    checks: Vec<AnimLabelCheck>,
+    /// spritesheets that were generated from their data since the checks last
+    /// ran, along with the path of the data
+    generated: Vec<(AssetId<Spritesheet>, AssetPath<'static>)>,
+}
+
+struct AnimLabelCheck {
+    path: AssetPath<'static>,
+    labels: &'static str,
+    verify: fn(&Spritesheet) -> Result<(), AnimLabelMismatch>,
 }
 
 // Implementations: -----------------------------------------------------------
@@ -48,11 +143,33 @@ impl Plugin for AsepritesheetPlugin {
         app.register_asset_loader(SpritesheetAssetLoader {
             extensions: self.extensions.clone(),
         })
+        .register_asset_loader(AsepriteAssetLoader)
+        .register_asset_loader(SpritesheetLoader {
+            extensions: [self.extensions.as_slice(), &["aseprite", "ase"]].concat(),
+        })
+        .register_asset_loader(AnimGraphAssetLoader)
         .init_asset::<SpritesheetData>()
         .init_asset::<Spritesheet>()
+        .init_asset::<AnimGraph>()
         .add_event::<AnimFinishEvent>()
+        .add_event::<AnimFrameEvent>()
+        .add_event::<AnimStartedEvent>()
+        .add_event::<AnimLoopedEvent>()
+        .add_event::<AnimFrameChangedEvent>()
         .add_event::<SpritesheetLoadedEvent>()
-        .add_systems(PreUpdate, handle_spritesheet_loading);
+        .add_event::<SpritesheetLoadFailedEvent>()
+        .add_event::<AnimLabelMismatchEvent>()
+        .init_resource::<LoadedSpritesheets>()
+        .init_resource::<AnimLabelChecks>()
+        .add_systems(
+            PreUpdate,
+            (
+                handle_spritesheet_loading,
+                reload_modified_spritesheets,
+                verify_loaded_anim_labels,
+            )
+                .chain(),
+        );
         if let Some(schedule) = self.schedule {
             app.insert_resource(SpriteAnimController::default())
                 .configure_sets(
@@ -64,20 +181,71 @@ impl Plugin for AsepritesheetPlugin {
                 .add_systems(
                     schedule,
                     (
+                        mark_modified_sheet_entities
+                            .before(add_needed_atlas_handles)
+                            .before(add_needed_img_handles),
                         add_needed_atlas_handles,
                         add_needed_img_handles,
+                        (sync_anim_graphs, update_anim_state_machines)
+                            .chain()
+                            .before(AnimationSet),
                         animate_sprites.in_set(AnimationSet),
                     ),
                 );
         } else {
             app.add_systems(
                 PreUpdate,
-                (add_needed_atlas_handles, add_needed_img_handles),
+                (
+                    mark_modified_sheet_entities
+                        .before(add_needed_atlas_handles)
+                        .before(add_needed_img_handles),
+                    add_needed_atlas_handles,
+                    add_needed_img_handles,
+                    (sync_anim_graphs, update_anim_state_machines).chain(),
+                ),
             );
         }
     }
 }
 
+impl AnimLabelsAppExt for App {
+    fn verify_anim_labels<L: AnimLabel>(
+        &mut self,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AnimLabelChecks::default)
+            .checks
+            .push(AnimLabelCheck {
+                path: path.into(),
+                labels: std::any::type_name::<L>(),
+                verify: Spritesheet::verify_anim_labels::<L>,
+            });
+        self
+    }
+}
+
+impl SpritesheetCallback {
+    /// Execute the callback on the specified spritesheet. Callbacks that need
+    /// access to the world are deferred until the commands are applied
+    fn run(&self, sheet: &mut Spritesheet, handle: &Handle<Spritesheet>, commands: &mut Commands) {
+        match self {
+            SpritesheetCallback::Sheet(callback) => (callback)(sheet),
+            SpritesheetCallback::World(callback) => {
+                let callback = callback.clone();
+                let handle = handle.clone();
+                commands.add(move |world: &mut World| {
+                    world.resource_scope(|world, mut sheets: Mut<Assets<Spritesheet>>| {
+                        if let Some(sheet) = sheets.get_mut(&handle) {
+                            (callback)(sheet, world);
+                        }
+                    });
+                });
+            }
+        }
+    }
+}
+
 impl Default for SpriteAnimController {
     fn default() -> Self {
         Self {
@@ -110,13 +278,17 @@ impl AsepritesheetPlugin {
 
 // Systems: -------------------------------------------------------------------
 
+#[allow(clippy::too_many_arguments)]
 fn handle_spritesheet_loading(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     spritesheet_data_assets: Res<Assets<SpritesheetData>>,
     mut load_event_writer: EventWriter<SpritesheetLoadedEvent>,
+    mut fail_event_writer: EventWriter<SpritesheetLoadFailedEvent>,
     mut spritesheet_assets: ResMut<Assets<Spritesheet>>,
     mut atlas_assets: ResMut<Assets<TextureAtlasLayout>>,
+    mut loaded_sheets: ResMut<LoadedSpritesheets>,
+    mut label_checks: ResMut<AnimLabelChecks>,
     query: Query<(
         Entity,
         &Handle<SpritesheetData>,
@@ -127,6 +299,17 @@ fn handle_spritesheet_loading(
 ) {
     // iterate through each loader
     for (ent, handle_spr_dat, handle_spr, anchor, loader) in &query {
+        // destroy the loader entity if the data can't be loaded, and send the
+        // event signal that it failed
+        if asset_server.get_load_state(handle_spr_dat) == Some(LoadState::Failed) {
+            commands.entity(ent).despawn();
+            fail_event_writer.send(SpritesheetLoadFailedEvent {
+                handle: handle_spr.clone(),
+                data_handle: handle_spr_dat.clone(),
+            });
+            continue;
+        }
+
         // if it's loaded
         if let Some(spr_data) = spritesheet_data_assets.get(handle_spr_dat) {
             if let Some(sheet) = spritesheet_assets.get_mut(handle_spr) {
@@ -135,8 +318,22 @@ fn handle_spritesheet_loading(
                 sheet.create_atlas_handle(&mut atlas_assets);
 
                 // execute callback
-                if let Some(callback) = loader.on_complete {
-                    (callback)(sheet);
+                if let Some(callback) = &loader.on_complete {
+                    callback.run(sheet, handle_spr, &mut commands);
+                }
+
+                // keep track of the spritesheet so it can be rebuilt if the
+                // data is modified
+                loaded_sheets.0.push(LoadedSpritesheet {
+                    data_handle: handle_spr_dat.clone(),
+                    sheet_handle: handle_spr.clone_weak(),
+                    anchor: *anchor,
+                    on_complete: loader.on_complete.clone(),
+                });
+                if let Some(path) = asset_server.get_path(handle_spr_dat) {
+                    label_checks
+                        .generated
+                        .push((handle_spr.id(), path.into_owned()));
                 }
 
                 // destroy loader entity and send the event signal that it's finished loading
@@ -150,6 +347,132 @@ fn handle_spritesheet_loading(
     }
 }
 
+/// Rebuild the spritesheets whose data was modified, such as when the file was
+/// hot reloaded. The callbacks they were loaded with are executed again, and
+/// the animators using them keep playing the same animations by name
+#[allow(clippy::too_many_arguments)]
+fn reload_modified_spritesheets(
+    mut commands: Commands,
+    mut data_events: EventReader<AssetEvent<SpritesheetData>>,
+    asset_server: Res<AssetServer>,
+    spritesheet_data_assets: Res<Assets<SpritesheetData>>,
+    mut spritesheet_assets: ResMut<Assets<Spritesheet>>,
+    mut atlas_assets: ResMut<Assets<TextureAtlasLayout>>,
+    mut loaded_sheets: ResMut<LoadedSpritesheets>,
+    mut label_checks: ResMut<AnimLabelChecks>,
+    mut animators: Query<(Entity, &Handle<Spritesheet>, &mut SpriteAnimator)>,
+) {
+    // forget the spritesheets that are no longer used
+    loaded_sheets
+        .0
+        .retain(|loaded| spritesheet_assets.contains(&loaded.sheet_handle));
+
+    for evt in data_events.read() {
+        let AssetEvent::Modified { id } = evt else {
+            continue;
+        };
+        let Some(spr_data) = spritesheet_data_assets.get(*id) else {
+            continue;
+        };
+        for loaded in loaded_sheets
+            .0
+            .iter()
+            .filter(|loaded| loaded.data_handle.id() == *id)
+        {
+            let Some(sheet) = spritesheet_assets.get_mut(&loaded.sheet_handle) else {
+                continue;
+            };
+
+            // rebuild the spritesheet, remembering the animations that the
+            // animators using it were playing
+            let mut playing = Vec::new();
+            for (ent, sheet_handle, mut animator) in &mut animators {
+                if sheet_handle.id() != loaded.sheet_handle.id() {
+                    continue;
+                }
+                animator.resolve_kept_anims(sheet);
+                if let Some(anim) = animator
+                    .cur_anim()
+                    .and_then(|handle| sheet.get_anim(&handle).ok())
+                {
+                    playing.push((ent, anim.name.clone()));
+                }
+            }
+            sheet.copy_from_with_image(spr_data, &loaded.anchor, &asset_server);
+            sheet.create_atlas_handle(&mut atlas_assets);
+            if let Some(callback) = &loaded.on_complete {
+                callback.run(sheet, &loaded.sheet_handle, &mut commands);
+            }
+            if let Some(path) = asset_server.get_path(*id) {
+                label_checks
+                    .generated
+                    .push((loaded.sheet_handle.id(), path.into_owned()));
+            }
+
+            // keep the animators on the same animations
+            for (ent, name) in playing {
+                let Ok((_, _, mut animator)) = animators.get_mut(ent) else {
+                    continue;
+                };
+                let new_anim = sheet.get_anim_handle(&name);
+                if sheet.get_anim(&new_anim).is_ok() {
+                    animator.retarget_anim(new_anim);
+                } else {
+                    warn!(
+                        "Animation '{}' was removed from the reloaded spritesheet",
+                        name
+                    );
+                    animator.stop_anim();
+                }
+            }
+        }
+    }
+}
+
+/// Check the labels registered with [`AnimLabelsAppExt::verify_anim_labels`]
+/// against the spritesheets that were just loaded, both the ones loaded as
+/// assets and the ones generated through [`load_spritesheet`] or similar
+/// functions. It runs after the commands of the load callbacks are applied, so
+/// that aliases added by the callbacks are taken into account
+fn verify_loaded_anim_labels(
+    mut sheet_events: EventReader<AssetEvent<Spritesheet>>,
+    asset_server: Res<AssetServer>,
+    spritesheet_assets: Res<Assets<Spritesheet>>,
+    mut label_checks: ResMut<AnimLabelChecks>,
+    mut mismatch_event_writer: EventWriter<AnimLabelMismatchEvent>,
+) {
+    let mut loaded = std::mem::take(&mut label_checks.generated);
+    for evt in sheet_events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } = evt {
+            if let Some(path) = asset_server.get_path(*id) {
+                loaded.push((*id, path.into_owned()));
+            }
+        }
+    }
+    if label_checks.checks.is_empty() {
+        return;
+    }
+
+    for (id, path) in loaded {
+        let Some(sheet) = spritesheet_assets.get(id) else {
+            continue;
+        };
+        for check in label_checks
+            .checks
+            .iter()
+            .filter(|check| check.path == path)
+        {
+            if let Err(mismatch) = (check.verify)(sheet) {
+                mismatch_event_writer.send(AnimLabelMismatchEvent {
+                    id,
+                    labels: check.labels,
+                    mismatch,
+                });
+            }
+        }
+    }
+}
+
 // Utility: -------------------------------------------------------------------
 
 /// a utility function used to load a spritesheet and optionally do some processing on it
@@ -159,15 +482,19 @@ fn handle_spritesheet_loading(
 /// found in the spritesheet
 ///
 /// # Arguments
-/// * `asset_server` the asset server from bevy that is used to load the asset from the sp
-///     specified path
+/// * `asset_server` the asset server from bevy that is used to load the asset from the
+///   specified path
 /// * `path` the asset path that points to the spritesheet file
-/// * `on_load` a closure that executes when the spritesheet data has finished loading and the
-///     spritesheet is created from the data. The [`Spritesheet`] is passed in as a mutable
-///     reference so that you can modify it's animations or do whatever else you need to
+/// * `on_load` a function that executes when the spritesheet data has finished loading and the
+///   spritesheet is created from the data. The [`Spritesheet`] is passed in as a mutable
+///   reference so that you can modify it's animations or do whatever else you need to. It's
+///   executed again whenever the spritesheet is hot reloaded
 ///
 /// # Example
 /// ```
+/// # use bevy::{prelude::*, sprite::Anchor};
+/// # use bevy_asepritesheet::prelude::*;
+/// # fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 /// load_spritesheet_then(
 ///     &mut commands,
 ///     &asset_server,
@@ -176,10 +503,11 @@ fn handle_spritesheet_loading(
 ///     |sheet|{
 ///         let attack = sheet.get_anim_handle("attack");
 ///         let idle = sheet.get_anim_handle("idle");
-///         if let Ok(attack_anim) = sheet.get_anim_mut(attack) {
+///         if let Ok(attack_anim) = sheet.get_anim_mut(&attack) {
 ///             attack_anim.end_action = AnimEndAction::Next(idle);
 ///         }
 ///     });
+/// # }
 /// ```
 pub fn load_spritesheet_then<'a>(
     commands: &mut Commands,
@@ -188,17 +516,59 @@ pub fn load_spritesheet_then<'a>(
     frame_anchor: Anchor,
     on_load: fn(&mut Spritesheet),
 ) -> Handle<Spritesheet> {
-    let spr_dat_handle = asset_server.load::<SpritesheetData>(path);
-    let spr_handle = asset_server.add::<Spritesheet>(default());
-    commands.spawn((
-        spr_dat_handle.clone(),
-        spr_handle.clone(),
+    spawn_spritesheet_loader(
+        commands,
+        asset_server,
+        path,
         frame_anchor,
-        SpriteSheetLoader {
-            on_complete: Some(on_load),
-        },
-    ));
-    spr_handle
+        Some(SpritesheetCallback::Sheet(on_load)),
+    )
+}
+
+/// The same as [`load_spritesheet_then`], except that the callback can capture values and is
+/// given access to the [`World`], so it can read resources or other assets while it modifies the
+/// spritesheet. The callback is executed when commands are applied after the spritesheet is
+/// created, and again whenever it's hot reloaded. NOTE: the [`Assets<Spritesheet>`] resource is
+/// not available from the world while the callback is executing
+///
+/// # Example
+/// ```
+/// # use bevy::{prelude::*, sprite::Anchor};
+/// # use bevy_asepritesheet::prelude::*;
+/// # #[derive(Resource)]
+/// # struct Settings { attack_speed: f32 }
+/// # fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// let recover = "recover".to_string();
+/// load_spritesheet_with(
+///     &mut commands,
+///     &asset_server,
+///     "my_spritesheet.sprite.json",
+///     Anchor::Center,
+///     move |sheet, world| {
+///         let attack = sheet.get_anim_handle("attack");
+///         let next = sheet.get_anim_handle(&recover);
+///         let speed = world.resource::<Settings>().attack_speed;
+///         if let Ok(attack_anim) = sheet.get_anim_mut(&attack) {
+///             attack_anim.end_action = AnimEndAction::Next(next);
+///             attack_anim.time_scale = speed;
+///         }
+///     });
+/// # }
+/// ```
+pub fn load_spritesheet_with<'a>(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    path: impl Into<AssetPath<'a>>,
+    frame_anchor: Anchor,
+    on_load: impl Fn(&mut Spritesheet, &mut World) + Send + Sync + 'static,
+) -> Handle<Spritesheet> {
+    spawn_spritesheet_loader(
+        commands,
+        asset_server,
+        path,
+        frame_anchor,
+        Some(SpritesheetCallback::World(Arc::new(on_load))),
+    )
 }
 
 /// A utility function used to load a spritesheet and return the handle. The [`SpritesheetData`]
@@ -207,17 +577,82 @@ pub fn load_spritesheet_then<'a>(
 /// found in the spritesheet
 ///
 /// # Arguments
-/// * `asset_server` the asset server from bevy that is used to load the asset from the sp
-///     specified path
+/// * `asset_server` the asset server from bevy that is used to load the asset from the
+///   specified path
 /// * `path` the asset path that points to the spritesheet file
-/// * `on_load` a closure that executes when the spritesheet data has finished loading and the
-///     spritesheet is created from the data. The [`Spritesheet`] is passed in as a mutable
-///     reference so that you can modify it's animations or do whatever else you need to
+/// * `frame_anchor` the anchor of each frame, relative to the untrimmed frame
+///
+/// The spritesheet is an empty placeholder until the data is loaded, which the asset server
+/// reports as loaded straight away, so listen for [`SpritesheetLoadedEvent`] to know when it has
+/// been generated
 pub fn load_spritesheet<'a>(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     path: impl Into<AssetPath<'a>>,
     frame_anchor: Anchor,
+) -> Handle<Spritesheet> {
+    spawn_spritesheet_loader(commands, asset_server, path, frame_anchor, None)
+}
+
+/// Give the animated sprite entity a different spritesheet, such as a palette or equipment
+/// variant of the same character. The image and texture atlas of the sprite are updated once the
+/// spritesheet has loaded, and the [`SpriteAnimator`] keeps playing the animation with the same
+/// name from the same time, so the swap is seamless. Queued animations and the animation to return
+/// to are remapped by name as well. A warning is logged if the new spritesheet doesn't have the
+/// animation. Inserting a different [`Handle<Spritesheet>`] on the entity has the same effect, as
+/// long as the animator has already played its animations on the old spritesheet
+///
+/// # Example
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_asepritesheet::prelude::*;
+/// # fn equip(mut commands: Commands, asset_server: Res<AssetServer>, player: Entity) {
+/// let armored: Handle<Spritesheet> = asset_server.load("witch_armored.sprite.json");
+/// swap_spritesheet(&mut commands, player, armored);
+/// # }
+/// ```
+pub fn swap_spritesheet(commands: &mut Commands, entity: Entity, sheet: Handle<Spritesheet>) {
+    commands.add(move |world: &mut World| {
+        world.resource_scope(|world, sheet_assets: Mut<Assets<Spritesheet>>| {
+            let Some(mut ent) = world.get_entity_mut(entity) else {
+                return;
+            };
+
+            // remember the names of the animations the animator keeps on the old spritesheet, and
+            // remap them now if the new spritesheet is loaded, otherwise the animator remaps them
+            // by name when it's animated
+            let old_sheet = ent
+                .get::<Handle<Spritesheet>>()
+                .and_then(|handle| sheet_assets.get(handle));
+            let new_sheet = sheet_assets.get(&sheet);
+            if let (Some(old_sheet), Some(mut animator)) =
+                (old_sheet, ent.get_mut::<SpriteAnimator>())
+            {
+                animator.resolve_kept_anims(old_sheet);
+                if let Some(new_sheet) = new_sheet {
+                    let new_anim = animator
+                        .cur_anim()
+                        .map(|cur_anim| cur_anim.retarget(old_sheet, new_sheet))
+                        .filter(|new_anim| new_sheet.get_anim(new_anim).is_ok());
+                    if let Some(new_anim) = new_anim {
+                        animator.retarget_anim(new_anim);
+                    }
+                    animator.resolve_kept_anims(new_sheet);
+                }
+            }
+            ent.insert(sheet);
+        });
+    });
+}
+
+/// Start loading the spritesheet data, and spawn a loader entity which
+/// generates the spritesheet once the data is loaded
+fn spawn_spritesheet_loader<'a>(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    path: impl Into<AssetPath<'a>>,
+    frame_anchor: Anchor,
+    on_complete: Option<SpritesheetCallback>,
 ) -> Handle<Spritesheet> {
     let spr_dat_handle = asset_server.load::<SpritesheetData>(path);
     let spr_handle = asset_server.add::<Spritesheet>(default());
@@ -225,7 +660,144 @@ pub fn load_spritesheet<'a>(
         spr_dat_handle.clone(),
         spr_handle.clone(),
         frame_anchor,
-        SpriteSheetLoader { on_complete: None },
+        SpriteSheetLoader { on_complete },
     ));
     spr_handle
 }
+
+// Tests: ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{asset::RecursiveDependencyLoadState, render::texture::ImagePlugin};
+
+    /// An app that loads assets from the crate's asset folder, without
+    /// rendering anything
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ImagePlugin::default(),
+            AsepritesheetPlugin::new(&["sprite.json"]),
+        ))
+        .init_asset::<TextureAtlasLayout>();
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    /// Update the app until the condition is met, or panic if it takes too
+    /// long
+    fn update_until(app: &mut App, condition: impl Fn(&mut World) -> bool) {
+        for _ in 0..500 {
+            app.update();
+            if condition(&mut app.world) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        panic!("timed out waiting for the assets to load");
+    }
+
+    #[test]
+    fn spritesheets_finish_loading_with_dependencies() {
+        #[derive(Resource)]
+        struct Sheets(Handle<Spritesheet>, Handle<Spritesheet>);
+
+        let mut app = test_app();
+        app.add_systems(
+            Startup,
+            |mut commands: Commands, asset_server: Res<AssetServer>| {
+                let generated = load_spritesheet(
+                    &mut commands,
+                    &asset_server,
+                    "witch.sprite.json",
+                    Anchor::Center,
+                );
+                let direct = asset_server.load("witch.sprite.json");
+                commands.insert_resource(Sheets(generated, direct));
+            },
+        );
+
+        // wait for both spritesheets and their images to be loaded
+        update_until(&mut app, |world| {
+            let sheets = world.resource::<Sheets>();
+            let sheet_assets = world.resource::<Assets<Spritesheet>>();
+            let image_assets = world.resource::<Assets<Image>>();
+            [&sheets.0, &sheets.1].into_iter().all(|handle| {
+                sheet_assets
+                    .get(handle)
+                    .is_some_and(|sheet| image_assets.contains(sheet.img_handle()))
+            })
+        });
+
+        let sheets = app.world.resource::<Sheets>();
+        let asset_server = app.world.resource::<AssetServer>();
+        for handle in [&sheets.0, &sheets.1] {
+            assert_eq!(
+                asset_server.get_recursive_dependency_load_state(handle),
+                Some(RecursiveDependencyLoadState::Loaded)
+            );
+        }
+    }
+
+    #[test]
+    fn swap_spritesheet_remaps_queue() {
+        use crate::animator::tests::{step, test_sheet};
+        use bevy::ecs::system::CommandQueue;
+
+        let old = test_sheet(&[
+            ("attack", 0, 1, "forward"),
+            ("recover", 2, 3, "forward"),
+            ("idle", 4, 5, "forward"),
+        ]);
+        let reordered = &[
+            ("idle", 0, 1, "forward"),
+            ("attack", 2, 3, "forward"),
+            ("recover", 4, 5, "forward"),
+        ];
+        let mut world = World::new();
+        let mut sheets = Assets::<Spritesheet>::default();
+        let attack = old.get_anim_handle("attack");
+        let recover = old.get_anim_handle("recover");
+        let old_handle = sheets.add(old);
+        let new_handle = sheets.add(test_sheet(reordered));
+        let unloaded_handle = sheets.reserve_handle();
+        world.insert_resource(sheets);
+        let swap = |world: &mut World, entity: Entity, sheet: &Handle<Spritesheet>| {
+            let mut queue = CommandQueue::default();
+            swap_spritesheet(&mut Commands::new(&mut queue, world), entity, sheet.clone());
+            queue.apply(world);
+        };
+        let play = |world: &mut World, entity: Entity, delta: f32| {
+            world.resource_scope(|world, sheets: Mut<Assets<Spritesheet>>| {
+                let mut ent = world.entity_mut(entity);
+                let sheet = sheets.get(ent.get::<Handle<Spritesheet>>().unwrap());
+                let mut animator = ent.get_mut::<SpriteAnimator>().unwrap();
+                step(&mut animator, sheet.unwrap(), delta)
+            })
+        };
+
+        // swap with an animation queued that the animator hasn't played yet,
+        // both to a loaded spritesheet and to one that is loaded after the swap
+        for sheet in [&new_handle, &unloaded_handle] {
+            let entity = world
+                .spawn((old_handle.clone(), SpriteAnimator::from_anim(attack)))
+                .id();
+            play(&mut world, entity, 0.05);
+            let mut animator = world.get_mut::<SpriteAnimator>(entity).unwrap();
+            animator.queue_anim(recover);
+            swap(&mut world, entity, sheet);
+            if !world.resource::<Assets<Spritesheet>>().contains(sheet) {
+                world
+                    .resource_mut::<Assets<Spritesheet>>()
+                    .insert(sheet, test_sheet(reordered));
+            }
+            assert_eq!(play(&mut world, entity, 0.0), 2);
+            assert_eq!(play(&mut world, entity, 0.1), 3);
+            assert_eq!(play(&mut world, entity, 0.1), 4);
+        }
+    }
+}
@@ -1,18 +1,31 @@
+pub mod anim_graph;
 pub mod animator;
 pub mod aseprite_data;
+pub mod aseprite_file;
 pub mod assets;
 pub mod core;
 pub mod sprite;
+pub mod state_machine;
 
 /// general purpose crate to re-export common types as a shortcut
 pub mod prelude {
+    pub use crate::anim_graph::AnimGraph;
+    pub use crate::anim_labels;
     pub use crate::animator::{
-        AnimEventSender, AnimFinishEvent, AnimatedSpriteBundle, SpriteAnimator,
+        AnimEventSender, AnimFinishEvent, AnimFrameChangedEvent, AnimFrameEvent, AnimLoopedEvent,
+        AnimStartedEvent, AnimTimestamp, AnimatedSpriteBundle, RangeMode, SliceColliders,
+        SpriteAnimator, TransitionMode,
     };
     pub use crate::aseprite_data::SpritesheetData;
+    pub use crate::assets::{AnimGraphLoadError, SpritesheetLoadError, SpritesheetLoaderSettings};
     pub use crate::core::{
-        load_spritesheet, load_spritesheet_then, AsepritesheetPlugin, SpriteAnimController,
-        SpritesheetLoadedEvent,
+        load_spritesheet, load_spritesheet_then, load_spritesheet_with, swap_spritesheet,
+        AnimLabelMismatchEvent, AnimLabelsAppExt, AsepritesheetPlugin, SpriteAnimController,
+        SpritesheetLoadFailedEvent, SpritesheetLoadedEvent,
     };
-    pub use crate::sprite::{AnimEndAction, AnimHandle, Spritesheet};
+    pub use crate::sprite::{
+        AnchorDef, AnimConfig, AnimDirection, AnimEndAction, AnimEndActionDef, AnimHandle,
+        AnimLabel, AnimLabelMismatch, AnimSettings, Spritesheet,
+    };
+    pub use crate::state_machine::{AnimCondition, AnimParam, AnimStateMachine, AnimTransition};
 }
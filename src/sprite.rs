@@ -1,24 +1,49 @@
-use crate::aseprite_data;
+use crate::{animator::SliceColliders, aseprite_data};
 use aseprite_data::SpritesheetData;
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{
+    asset::AssetPath,
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+    sprite::Anchor,
+    utils::HashMap,
+};
 use serde::{Deserialize, Serialize};
-use std::{ops::*, usize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::*,
+    sync::atomic::{AtomicU64, Ordering},
+    usize,
+};
+use thiserror::Error;
 
 // Struct Definitions: ---------------------------------------------------------
 
 /// A spritesheet object containing processed data from the deserialized
 /// aseprite data. Used as reference data for the
 /// [`crate::sprite_animator::SpriteAnimator`] component
-#[derive(Asset, Reflect, Default, Clone, Debug)]
+#[derive(Asset, Reflect, Default, Debug)]
 // #[uuid = "13361c8f-a7f0-4db8-8492-c3d5387ffa7b"]
 pub struct Spritesheet {
     /// A set of every possible frame that can be used for an animation within
     /// the spritesheet
     pub frames: Vec<Frame>,
     anims: Vec<Anim>,
-    img_handle: Handle<Image>,
+    slices: Vec<Slice>,
+    /// [`None`] until the spritesheet has an image, so that the placeholder
+    /// added by [`crate::core::load_spritesheet`] has no dependencies
+    #[dependency]
+    img_handle: Option<Handle<Image>>,
     img_size: Vec2,
+    #[dependency]
     atlas_handle: Option<Handle<TextureAtlasLayout>>,
+    /// The index in `anims` of each animation name and alias
+    anim_index: HashMap<String, usize>,
+    /// Identifies the spritesheet, so that handles to its animations can't be
+    /// used on other spritesheets. It stays the same when the spritesheet is
+    /// rebuilt from new data, such as when it's hot reloaded, but clones get
+    /// their own
+    sheet_id: u64,
 }
 
 /// A parsed spritesheet animation that determines which sprite frames will be
@@ -36,7 +61,16 @@ pub struct Anim {
     /// How the animation behaves when it reaches the end
     pub end_action: AnimEndAction,
 
-    /// A set of the individual frame indices in the sprite frame set
+    /// The user data attached to the frame tag in aseprite, it's sent as an
+    /// [`crate::animator::AnimFrameEvent`] whenever the animation enters its
+    /// first frame
+    pub user_data: Option<UserData>,
+
+    /// The direction that the frames of the animation are played in
+    direction: AnimDirection,
+
+    /// A set of the individual frame indices in the sprite frame set, in
+    /// the order that they are played
     frames_indices: Vec<usize>,
 
     /// The total length of the animation in seconds
@@ -44,16 +78,26 @@ pub struct Anim {
 }
 
 /// A handle for [`Anim`] that can be used as a reference to play specific
-/// animations on a spritesheet
+/// animations on a spritesheet. Handles from [`Spritesheet::get_anim_handle`]
+/// only refer to animations on the spritesheet they came from, see
+/// [`AnimHandle::retarget`] for finding the same animation on another one.
+/// Handles created with [`AnimHandle::from_index`] match the handles of any
+/// spritesheet with the same index, see [`AnimHandle::matches`]
 #[derive(Default, Clone, Reflect, Copy, Debug, PartialEq, Eq)]
 pub struct AnimHandle {
     index: Option<usize>,
+    /// The id of the spritesheet the handle is for, or 0 for any
+    sheet: u64,
 }
 
 /// An animation frame, a single atomic piece of an animation, that holds
 /// information about the sprite as it should appear when the frame is active
 #[derive(Clone, Reflect, Debug)]
 pub struct Frame {
+    /// the filename of the frame in the aseprite data, this is also the key
+    /// of the frame when exported with the "Hash" json layout
+    pub filename: String,
+
     /// the index of the sprite frame rect on the texture atlas
     pub atlas_index: usize,
 
@@ -63,11 +107,62 @@ pub struct Frame {
     /// the anchor point of the frame
     pub anchor: Anchor,
 
+    /// the user data attached to the cels of the frame in aseprite, each is
+    /// sent as an [`crate::animator::AnimFrameEvent`] whenever an animation
+    /// enters the frame
+    pub user_data: Vec<UserData>,
+
     /// the frame rect - only used when building the texture atlas, maybe
     /// not necessary?
     rect: Rect, // TODO remove ?
 }
 
+/// User data attached to a frame tag or cel in aseprite, used to author
+/// events such as footsteps directly in aseprite
+#[derive(Clone, Reflect, Debug, Default, PartialEq)]
+pub struct UserData {
+    /// The user data text, empty if only a color was specified
+    pub text: String,
+
+    /// The user data color, if one was specified
+    pub color: Option<Color>,
+}
+
+/// A slice defined in aseprite, such as a hitbox or interaction zone, which
+/// can change shape from frame to frame
+#[derive(Clone, Reflect, Debug)]
+pub struct Slice {
+    /// The name of the slice as defined in aseprite
+    pub name: String,
+
+    /// The color that the slice is displayed with in aseprite
+    pub color: Color,
+
+    /// The user data text attached to the slice in aseprite, if any
+    pub data: Option<String>,
+
+    /// The shape of the slice starting at each keyframe, sorted by frame
+    keys: Vec<SliceKey>,
+}
+
+/// The shape of a [`Slice`] starting at a specific frame. All positions are in
+/// pixels, relative to the anchor point of the frames, with y pointing up like
+/// the rest of bevy
+#[derive(Clone, Copy, Reflect, Debug, PartialEq)]
+pub struct SliceKey {
+    /// The index of the frame that the key starts at
+    pub frame: usize,
+
+    /// The bounds of the slice
+    pub rect: Rect,
+
+    /// The 9-patch center of the slice, if it has one
+    pub center: Option<Rect>,
+
+    /// The pivot point of the slice, if it has one
+    pub pivot: Option<Vec2>,
+}
+
 /// Enum for setting different end behaviors of a sprite's animation,
 /// default is [`AnimEndAction::Loop`]
 #[allow(dead_code)]
@@ -75,16 +170,164 @@ pub struct Frame {
 pub enum AnimEndAction {
     /// Stop the animation after completion, sets current animation to [`None`]
     Stop,
-    // Pause the animator after completion
+    /// Pause the animator on the last frame after completion, until another
+    /// animation is played or the animator is resumed, which plays the
+    /// animation again from the start, see
+    /// [`crate::animator::SpriteAnimator::resume`]
     Pause,
     // Loop through the animation, restarts from the beginning upon completion
     Loop,
     // After completion, play the next specified animation
     Next(AnimHandle),
+    /// Play the animation the specified amount of times in total, and then
+    /// stop it as with [`AnimEndAction::Stop`]. Frame tags with a repeat count
+    /// in aseprite use this end action
+    LoopCount(u32),
+    /// Despawn the entity after completion, for one-shot effects
+    Despawn,
+    /// Stop the animation and set the entity's [`Visibility`] to hidden
+    Hide,
+    /// Play the animation that was playing before this one from the start,
+    /// for animations that interrupt others, stops the animation if there
+    /// wasn't one
+    ReturnToPrevious,
+}
+
+/// A serializable [`AnimEndAction`] that refers to the next animation by name
+/// instead of by handle, so that it can be written in asset files before the
+/// spritesheet it applies to is loaded
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimEndActionDef {
+    /// See [`AnimEndAction::Stop`]
+    Stop,
+    /// See [`AnimEndAction::Pause`]
+    Pause,
+    /// See [`AnimEndAction::Loop`]
+    Loop,
+    /// Play the animation with the specified name after completion
+    Next(String),
+    /// See [`AnimEndAction::LoopCount`]
+    LoopCount(u32),
+    /// See [`AnimEndAction::Despawn`]
+    Despawn,
+    /// See [`AnimEndAction::Hide`]
+    Hide,
+    /// See [`AnimEndAction::ReturnToPrevious`]
+    ReturnToPrevious,
+}
+
+/// Serializable settings for an [`Anim`], used by asset files and loader
+/// settings. Settings that are not specified are left as they are
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimSettings {
+    /// What the animation does when it reaches the end
+    #[serde(default)]
+    pub end_action: Option<AnimEndActionDef>,
+    /// The speed multiplier of the animation
+    #[serde(default)]
+    pub time_scale: Option<f32>,
+}
+
+/// Animation settings for a spritesheet, read from a sidecar `.anim.ron` file
+/// next to the spritesheet file, for example `witch.sprite.anim.ron` for
+/// `witch.sprite.json`, or `witch.anim.ron` for `witch.aseprite`
+///
+/// # Example
+/// ```ron
+/// (
+///     anims: {
+///         "jump": (end_action: Next("falling")),
+///         "attack_light": (end_action: Next("idle"), time_scale: 1.5),
+///     },
+///     aliases: { "run": "running" },
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimConfig {
+    /// Settings for specific animations, by animation name
+    #[serde(default)]
+    pub anims: HashMap<String, AnimSettings>,
+    /// Alternative names that animations can be found by, mapped to the name
+    /// of the animation that they refer to
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+/// A serializable [`Anchor`], so that the frame anchor can be specified in
+/// asset files and loader settings, default is [`AnchorDef::Center`]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AnchorDef {
+    #[default]
+    Center,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    CenterLeft,
+    CenterRight,
+    TopLeft,
+    TopCenter,
+    TopRight,
+    /// A custom anchor point, see [`Anchor::Custom`]
+    Custom(f32, f32),
+}
+
+/// The direction that an animation plays its frames in, as specified by the
+/// frame tag direction in aseprite, default is [`AnimDirection::Forward`]
+#[derive(Default, Copy, Clone, Reflect, Debug, PartialEq, Eq)]
+pub enum AnimDirection {
+    /// Play the frames from first to last
+    #[default]
+    Forward,
+    /// Play the frames from last to first
+    Reverse,
+    /// Play the frames from first to last, and then back towards the first
+    PingPong,
+    /// Play the frames from last to first, and then back towards the last
+    PingPongReverse,
+}
+
+/// A type with a value for each animation of a spritesheet, so that
+/// animations can be referred to without typing their names, see
+/// [`crate::anim_labels`] for declaring an enum that implements it. Use
+/// [`crate::core::AnimLabelsAppExt::verify_anim_labels`] to make sure that the
+/// labels match the frame tags whenever the spritesheet is loaded
+pub trait AnimLabel: Copy + 'static {
+    /// Every value of the type
+    fn all() -> &'static [Self];
+    /// The name or alias of the animation that the label refers to
+    fn anim_name(&self) -> &'static str;
+}
+
+/// The differences between a set of [`AnimLabel`]s and the animations of a
+/// spritesheet, returned by [`Spritesheet::verify_anim_labels`]
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("missing animations: {missing:?}, animations without a label: {extra:?}")]
+pub struct AnimLabelMismatch {
+    /// The names of labels that aren't in the spritesheet
+    pub missing: Vec<String>,
+    /// The names of animations in the spritesheet that no label refers to
+    pub extra: Vec<String>,
 }
 
 // Struct Implementations: -----------------------------------------------------
 
+impl Clone for Spritesheet {
+    /// Clone the spritesheet with a new id, so that the handles to the
+    /// animations of each spritesheet can only be used on that spritesheet
+    fn clone(&self) -> Self {
+        Self {
+            frames: self.frames.clone(),
+            anims: self.anims.clone(),
+            slices: self.slices.clone(),
+            img_handle: self.img_handle.clone(),
+            img_size: self.img_size,
+            atlas_handle: self.atlas_handle.clone(),
+            anim_index: self.anim_index.clone(),
+            sheet_id: next_sheet_id(),
+        }
+    }
+}
+
 impl Spritesheet {
     /// Create a new spritesheet object from the specified data, should
     /// generally not be used unless you are generating spritesheets entirely
@@ -95,13 +338,18 @@ impl Spritesheet {
         img_handle: Handle<Image>,
         img_size: Vec2,
     ) -> Self {
-        Spritesheet {
-            frames: frames,
-            anims: anims,
-            img_handle: img_handle,
-            img_size: img_size,
+        let mut sheet = Spritesheet {
+            frames,
+            anims,
+            slices: Vec::new(),
+            img_handle: Some(img_handle),
+            img_size,
             atlas_handle: None,
-        }
+            anim_index: HashMap::default(),
+            sheet_id: next_sheet_id(),
+        };
+        sheet.rebuild_anim_index();
+        sheet
     }
 
     /// Create a new spritesheet from given aseprite json data and a specified
@@ -118,7 +366,7 @@ impl Spritesheet {
         // construct and return a spritesheet from the data given
         let mut sheet = Spritesheet::default();
         sheet.copy_from(data, frame_anchor);
-        sheet.img_handle = img_handle;
+        sheet.img_handle = Some(img_handle);
 
         // creat the atlas asset handle
         sheet.create_atlas_handle(atlas_assets);
@@ -148,9 +396,25 @@ impl Spritesheet {
 
     /// copy all the data from the specified spritesheet data into self
     pub fn copy_from(&mut self, data: &SpritesheetData, frame_anchor: &Anchor) {
+        // gather the user data of each cel by the frame that it's on
+        let mut frame_user_data = vec![Vec::<UserData>::new(); data.frames.len()];
+        for layer_data in &data.meta.layers {
+            for cel_data in &layer_data.cels {
+                let Some(user_data) = UserData::from_data(&cel_data.data, &cel_data.color) else {
+                    continue;
+                };
+                if let Some(frame_data) = frame_user_data.get_mut(cel_data.frame) {
+                    frame_data.push(user_data);
+                }
+            }
+        }
+
         // populate create a frames vec to store all frames in sprite data
         let mut frames = Vec::<Frame>::new();
         for (i, frame_data) in data.frames.iter().enumerate() {
+            // get the position of the anchor in the original frame
+            let anchor_pos = anchor_source_position(frame_anchor, frame_data.source_size);
+
             // get frame offset from original frame top left corner
             // (only relevant if frames were trimmed by aseprite in the export)
             let frame_offset = Vec2::new(
@@ -164,13 +428,7 @@ impl Spritesheet {
 
             // calculate the new sprite anchor based on how much the frame was
             // trimmed and moved by aseprite
-            let anchor_target = frame_anchor
-                .as_vec()
-                // offset by 0.5 since bevy considers <-0.5,-0.5> to be top left
-                // for some reason
-                .add(Vec2::splat(0.5))
-                // get original frame size data
-                .mul(Vec2::from(frame_data.source_size))
+            let anchor_target = anchor_pos
                 // correct for frame offset
                 .sub(frame_offset)
                 // scale anchor pos to new trimmed size
@@ -183,9 +441,11 @@ impl Spritesheet {
 
             // construct frame container from calculated data
             let frame = Frame {
+                filename: frame_data.filename.clone(),
                 atlas_index: i,
                 duration: frame_data.duration as f32 * 0.001,
                 anchor: Anchor::Custom(anchor_target),
+                user_data: std::mem::take(&mut frame_user_data[i]),
                 rect: frame_data.frame.into(),
             };
 
@@ -197,11 +457,27 @@ impl Spritesheet {
         let mut anims = Vec::<Anim>::new();
         for tag_data in &data.meta.frame_tags {
             // construct animation container from data
+            let direction = AnimDirection::from(tag_data.direction.as_str());
             let mut anim = Anim {
                 name: tag_data.name.clone(),
-                frames_indices: (tag_data.from..=tag_data.to).collect(),
+                frames_indices: direction.frame_indices(tag_data.from, tag_data.to),
                 time_scale: 1.0,
-                end_action: AnimEndAction::Loop,
+                // tags that repeat a limited amount of times have a repeat
+                // count, all others loop forever
+                end_action: match tag_data
+                    .repeat
+                    .as_ref()
+                    .and_then(|repeat| repeat.parse::<u32>().ok())
+                {
+                    Some(count) if count > 0 => AnimEndAction::LoopCount(count),
+                    _ => AnimEndAction::Loop,
+                },
+                // tags always have a color, so only the text makes it an event
+                user_data: tag_data
+                    .data
+                    .as_ref()
+                    .and_then(|_| UserData::from_data(&tag_data.data, &tag_data.color)),
+                direction,
                 total_time: 0.0,
             };
 
@@ -210,9 +486,85 @@ impl Spritesheet {
             anims.push(anim);
         }
 
+        // create and populate a vec for all the slices, all frames share the
+        // same source size so any frame can be used to find the anchor
+        let mut slices = Vec::<Slice>::new();
+        if let Some(frame_data) = data.frames.first() {
+            let anchor_pos = anchor_source_position(frame_anchor, frame_data.source_size);
+            for slice_data in &data.meta.slices {
+                let mut keys: Vec<SliceKey> = slice_data
+                    .keys
+                    .iter()
+                    .map(|key_data| SliceKey::from_data(key_data, anchor_pos))
+                    .collect();
+                keys.sort_by_key(|key| key.frame);
+                slices.push(Slice {
+                    name: slice_data.name.clone(),
+                    color: Color::hex(&slice_data.color).unwrap_or(Color::BLUE),
+                    data: slice_data.data.clone(),
+                    keys,
+                });
+            }
+        }
+
         self.frames = frames;
         self.anims = anims;
+        self.slices = slices;
         self.img_size = data.meta.size.into();
+        if self.sheet_id == 0 {
+            self.sheet_id = next_sheet_id();
+        }
+        self.rebuild_anim_index();
+
+        // apply the settings from the sidecar animation config, and then the
+        // settings that the data was loaded with
+        if let Some(config) = &data.anim_config {
+            self.apply_anim_config(config);
+        }
+        if let Some(settings) = &data.loader_settings {
+            settings.apply_to_sheet_with_config(self, data.anim_config.as_ref());
+        }
+    }
+
+    /// Apply the animation settings and aliases from the specified config
+    pub fn apply_anim_config(&mut self, config: &AnimConfig) {
+        for (name, settings) in &config.anims {
+            if !self.apply_anim_settings(name, settings) {
+                warn!("Animation config refers to missing animation '{}'", name);
+            }
+        }
+        for (alias, name) in &config.aliases {
+            if !self.add_anim_alias(alias, name) {
+                warn!(
+                    "Animation config alias '{}' refers to missing animation '{}'",
+                    alias, name
+                );
+            }
+        }
+    }
+
+    /// Add an alternative name that the animation with the specified name can
+    /// be found by with [`Spritesheet::get_anim_handle`], returns false if the
+    /// animation doesn't exist. Aliases never hide animation names
+    pub fn add_anim_alias(&mut self, alias: impl Into<String>, name: impl AsRef<str>) -> bool {
+        let Some(&index) = self.anim_index.get(name.as_ref()) else {
+            return false;
+        };
+        let alias = alias.into();
+        if !self.anims.iter().any(|anim| anim.name == alias) {
+            self.anim_index.insert(alias, index);
+        }
+        true
+    }
+
+    /// Rebuild the name index used by [`Spritesheet::get_anim_handle`] from
+    /// the animation names, which removes all aliases. When several
+    /// animations share a name, the first one is found
+    fn rebuild_anim_index(&mut self) {
+        self.anim_index.clear();
+        for (i, anim) in self.anims.iter().enumerate() {
+            self.anim_index.entry(anim.name.clone()).or_insert(i);
+        }
     }
 
     /// copy all the data from the specified spritesheet data into self + load and use the image
@@ -224,12 +576,28 @@ impl Spritesheet {
         asset_server: &Res<AssetServer>,
     ) {
         self.copy_from(data, frame_anchor);
-        self.img_handle = asset_server.load(&data.meta.image);
+
+        // images that are sub-assets, such as the image of an aseprite file,
+        // already have the sampler applied by their loader
+        let sampler = data
+            .loader_settings
+            .as_ref()
+            .map(|settings| settings.sampler.clone())
+            .filter(|_| AssetPath::parse(&data.meta.image).label().is_none());
+        self.img_handle = Some(match sampler {
+            None | Some(ImageSampler::Default) => asset_server.load(&data.meta.image),
+            Some(sampler) => asset_server.load_with_settings(
+                &data.meta.image,
+                move |img_settings: &mut ImageLoaderSettings| {
+                    img_settings.sampler = sampler.clone();
+                },
+            ),
+        });
     }
 
     /// Get the image handle that the spritesheet is using
     pub fn img_handle(&self) -> Handle<Image> {
-        self.img_handle.clone()
+        self.img_handle.clone().unwrap_or_default()
     }
 
     /// Get the dimensions of the image asset being used by the spritesheet
@@ -256,28 +624,117 @@ impl Spritesheet {
         if let Some(handle) = &self.atlas_handle {
             atlas_assets.remove(handle);
         }
+        let handle = atlas_assets.add(self.create_atlas_layout());
+        self.atlas_handle = Some(handle.clone());
+        handle
+    }
+
+    /// Create a texture atlas layout with each sprite frame on it, without
+    /// adding it to the spritesheet
+    pub fn create_atlas_layout(&self) -> TextureAtlasLayout {
         let mut atlas = TextureAtlasLayout::new_empty(self.img_size());
         for frame in &self.frames {
             atlas.add_texture(frame.rect.clone());
         }
-        let handle = atlas_assets.add(atlas);
-        self.atlas_handle = Some(handle.clone());
-        handle
+        atlas
+    }
+
+    /// Identify the spritesheet by the path of the asset that it's loaded
+    /// from, so that handles to its animations stay valid when the asset is
+    /// reloaded. Must be called before the spritesheet is built from its data
+    pub(crate) fn set_asset_path(&mut self, path: &AssetPath) {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        // the top bit keeps the ids from paths apart from the counted ids
+        self.sheet_id = hasher.finish() | 1 << 63;
+    }
+
+    /// The id that identifies the spritesheet, see [`AnimHandle::is_for_sheet`]
+    pub(crate) fn sheet_id(&self) -> u64 {
+        self.sheet_id
+    }
+
+    /// Set the image and texture atlas handles that the spritesheet uses
+    pub(crate) fn set_handles(
+        &mut self,
+        img_handle: Handle<Image>,
+        atlas_handle: Handle<TextureAtlasLayout>,
+    ) {
+        self.img_handle = Some(img_handle);
+        self.atlas_handle = Some(atlas_handle);
+    }
+
+    /// Get all the slices defined in the spritesheet
+    pub fn slices(&self) -> &[Slice] {
+        &self.slices
     }
 
-    /// Get a handle to the animation with the specified name, if it exists
+    /// Get the slice with the specified name, if it exists
+    pub fn get_slice<T: AsRef<str>>(&self, name: T) -> Option<&Slice> {
+        self.slices.iter().find(|slice| slice.name == name.as_ref())
+    }
+
+    /// Get every slice that is active on the frame at the specified index in
+    /// [`Spritesheet::frames`], along with the shape of the slice on that frame
+    pub fn frame_slices(&self, frame_index: usize) -> impl Iterator<Item = (&Slice, &SliceKey)> {
+        self.slices
+            .iter()
+            .filter_map(move |slice| slice.key_at(frame_index).map(|key| (slice, key)))
+    }
+
+    /// Get a handle to the animation with the specified name or alias, if it
+    /// exists
     pub fn get_anim_handle<T: AsRef<str>>(&self, name: T) -> AnimHandle {
-        for (i, anim) in self.anims.iter().enumerate() {
-            if anim.name == name.as_ref() {
-                return AnimHandle::from_index(i);
+        match self.anim_index.get(name.as_ref()) {
+            Some(&index) => AnimHandle {
+                index: Some(index),
+                sheet: self.sheet_id,
+            },
+            None => AnimHandle::invalid(),
+        }
+    }
+
+    /// Get a handle to the animation that the specified label refers to, if
+    /// it exists
+    pub fn get_label_handle<L: AnimLabel>(&self, label: L) -> AnimHandle {
+        self.get_anim_handle(label.anim_name())
+    }
+
+    /// Check that every label of `L` refers to an animation in the
+    /// spritesheet, and that every animation has a label. A warning listing
+    /// the missing and extra animations is logged if they don't match
+    pub fn verify_anim_labels<L: AnimLabel>(&self) -> Result<(), AnimLabelMismatch> {
+        let mut missing = Vec::new();
+        let mut labeled = vec![false; self.anims.len()];
+        for label in L::all() {
+            match self.anim_index.get(label.anim_name()) {
+                Some(&index) => labeled[index] = true,
+                None => missing.push(label.anim_name().to_string()),
             }
         }
-        AnimHandle::invalid()
+        let extra: Vec<String> = self
+            .anims
+            .iter()
+            .zip(labeled)
+            .filter(|(_, labeled)| !labeled)
+            .map(|(anim, _)| anim.name.clone())
+            .collect();
+        if missing.is_empty() && extra.is_empty() {
+            return Ok(());
+        }
+        let mismatch = AnimLabelMismatch { missing, extra };
+        warn!(
+            "Animation labels '{}' don't match the spritesheet, {}",
+            std::any::type_name::<L>(),
+            mismatch
+        );
+        Err(mismatch)
     }
 
-    /// Get a reference to the specified animation, if it exists
+    /// Get a reference to the specified animation, if it exists and the
+    /// handle is for this spritesheet
     pub fn get_anim(&self, handle: &AnimHandle) -> Result<&Anim, ()> {
-        if !handle.is_valid() {
+        if !handle.is_valid() || !handle.is_for_sheet(self) {
             return Err(());
         }
         let index = handle.index.unwrap();
@@ -288,9 +745,24 @@ impl Spritesheet {
         }
     }
 
-    /// Get a mutable reference to the specified animation, if it exists
+    /// Apply the specified settings to the animation with the specified name,
+    /// returns false if the animation doesn't exist
+    pub fn apply_anim_settings<T: AsRef<str>>(&mut self, name: T, settings: &AnimSettings) -> bool {
+        let handle = self.get_anim_handle(name);
+        let Ok(anim) = self.get_anim(&handle) else {
+            return false;
+        };
+        let (end_action, time_scale) = settings.resolve(self, anim);
+        let anim = &mut self.anims[handle.index.unwrap()];
+        anim.end_action = end_action;
+        anim.time_scale = time_scale;
+        true
+    }
+
+    /// Get a mutable reference to the specified animation, if it exists and
+    /// the handle is for this spritesheet
     pub fn get_anim_mut(&mut self, handle: &AnimHandle) -> Result<&mut Anim, ()> {
-        if !handle.is_valid() {
+        if !handle.is_valid() || !handle.is_for_sheet(self) {
             return Err(());
         }
         let index = handle.index.unwrap();
@@ -305,11 +777,17 @@ impl Spritesheet {
 #[allow(dead_code)]
 impl Anim {
     /// The set of indices referring to all the frames in the spritesheet that
-    /// the animation consists of
+    /// the animation consists of, in the order that they are played. For
+    /// ping-pong animations this includes the frames played on the way back
     pub fn frame_indices(&self) -> &Vec<usize> {
         &self.frames_indices
     }
 
+    /// The direction that the animation plays its frames in
+    pub fn direction(&self) -> AnimDirection {
+        self.direction
+    }
+
     /// The total amount of time that it takes to play the animation, in seconds
     /// NOTE: does not take into account time_scale
     pub fn total_time(&self) -> f32 {
@@ -325,17 +803,163 @@ impl Anim {
     }
 }
 
+impl UserData {
+    /// Create user data from the text and color exported by aseprite, if
+    /// either of them were specified
+    fn from_data(text: &Option<String>, color: &Option<String>) -> Option<Self> {
+        if text.is_none() && color.is_none() {
+            return None;
+        }
+        Some(UserData {
+            text: text.clone().unwrap_or_default(),
+            color: color.as_ref().and_then(|color| Color::hex(color).ok()),
+        })
+    }
+}
+
+impl Slice {
+    /// The shape of the slice at each keyframe, sorted by frame
+    pub fn keys(&self) -> &[SliceKey] {
+        &self.keys
+    }
+
+    /// The shape of the slice on the frame at the specified index, if the
+    /// slice is active on that frame. A key stays active until the next key,
+    /// and keys with an empty rect hide the slice
+    pub fn key_at(&self, frame_index: usize) -> Option<&SliceKey> {
+        self.keys
+            .iter()
+            .take_while(|key| key.frame <= frame_index)
+            .last()
+            .filter(|key| !key.rect.is_empty())
+    }
+}
+
+impl SliceKey {
+    /// Convert the slice key data into bevy coordinates relative to the
+    /// specified anchor position in source frame pixel coordinates
+    fn from_data(data: &aseprite_data::SliceKeyData, anchor_pos: Vec2) -> Self {
+        let bounds = Rect::from(data.bounds);
+        SliceKey {
+            frame: data.frame,
+            rect: source_rect_to_local(bounds, anchor_pos),
+            center: data.center.map(|center| {
+                let center = Rect::from(center);
+                source_rect_to_local(
+                    Rect::from_corners(center.min + bounds.min, center.max + bounds.min),
+                    anchor_pos,
+                )
+            }),
+            pivot: data
+                .pivot
+                .map(|pivot| source_point_to_local(Vec2::from(pivot) + bounds.min, anchor_pos)),
+        }
+    }
+}
+
+impl AnimDirection {
+    /// The order that the frames in the specified range (inclusive) are
+    /// played in for one full cycle of an animation with this direction.
+    /// Ping-pong cycles don't repeat the frames at either end, so that they
+    /// can loop seamlessly
+    pub fn frame_indices(&self, from: usize, to: usize) -> Vec<usize> {
+        let forward = from..=to;
+        match self {
+            AnimDirection::Forward => forward.collect(),
+            AnimDirection::Reverse => forward.rev().collect(),
+            AnimDirection::PingPong => forward.clone().chain((from..to).skip(1).rev()).collect(),
+            AnimDirection::PingPongReverse => {
+                forward.clone().rev().chain((from..to).skip(1)).collect()
+            }
+        }
+    }
+}
+
+impl From<&str> for AnimDirection {
+    /// Parse the frame tag direction string exported by aseprite, unknown
+    /// directions are treated as [`AnimDirection::Forward`]
+    fn from(value: &str) -> Self {
+        match value {
+            "reverse" => AnimDirection::Reverse,
+            "pingpong" => AnimDirection::PingPong,
+            "pingpong_reverse" => AnimDirection::PingPongReverse,
+            _ => AnimDirection::Forward,
+        }
+    }
+}
+
+impl AnimSettings {
+    /// The end action and time scale that the specified animation would have
+    /// with these settings applied to it
+    pub(crate) fn resolve(&self, sheet: &Spritesheet, anim: &Anim) -> (AnimEndAction, f32) {
+        let end_action = match &self.end_action {
+            Some(def) => def.resolve(sheet).unwrap_or_else(|| {
+                warn!("Invalid end action {:?} for animation '{}'", def, anim.name);
+                anim.end_action
+            }),
+            None => anim.end_action,
+        };
+        (end_action, self.time_scale.unwrap_or(anim.time_scale))
+    }
+}
+
+impl From<AnchorDef> for Anchor {
+    fn from(value: AnchorDef) -> Self {
+        match value {
+            AnchorDef::Center => Anchor::Center,
+            AnchorDef::BottomLeft => Anchor::BottomLeft,
+            AnchorDef::BottomCenter => Anchor::BottomCenter,
+            AnchorDef::BottomRight => Anchor::BottomRight,
+            AnchorDef::CenterLeft => Anchor::CenterLeft,
+            AnchorDef::CenterRight => Anchor::CenterRight,
+            AnchorDef::TopLeft => Anchor::TopLeft,
+            AnchorDef::TopCenter => Anchor::TopCenter,
+            AnchorDef::TopRight => Anchor::TopRight,
+            AnchorDef::Custom(x, y) => Anchor::Custom(Vec2::new(x, y)),
+        }
+    }
+}
+
+impl AnimEndActionDef {
+    /// Get the end action that this refers to on the specified spritesheet,
+    /// or [`None`] if the next animation doesn't exist on it
+    pub fn resolve(&self, sheet: &Spritesheet) -> Option<AnimEndAction> {
+        match self {
+            AnimEndActionDef::Stop => Some(AnimEndAction::Stop),
+            AnimEndActionDef::Pause => Some(AnimEndAction::Pause),
+            AnimEndActionDef::Loop => Some(AnimEndAction::Loop),
+            AnimEndActionDef::LoopCount(count) => Some(AnimEndAction::LoopCount(*count)),
+            AnimEndActionDef::Despawn => Some(AnimEndAction::Despawn),
+            AnimEndActionDef::Hide => Some(AnimEndAction::Hide),
+            AnimEndActionDef::ReturnToPrevious => Some(AnimEndAction::ReturnToPrevious),
+            AnimEndActionDef::Next(name) => {
+                let handle = sheet.get_anim_handle(name);
+                sheet
+                    .get_anim(&handle)
+                    .ok()
+                    .map(|_| AnimEndAction::Next(handle))
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 impl AnimHandle {
     /// Create an animation handle that refers to an animation of the specified
     /// index on any spritesheet
     pub fn from_index(index: usize) -> Self {
-        AnimHandle { index: Some(index) }
+        AnimHandle {
+            index: Some(index),
+            sheet: 0,
+        }
     }
 
     /// Create an invalid handle
     pub fn invalid() -> Self {
-        AnimHandle { index: None }
+        AnimHandle {
+            index: None,
+            sheet: 0,
+        }
     }
 
     /// Whether or not the handle is valid. NOTE just because it's valid does not mean that the
@@ -343,6 +967,119 @@ impl AnimHandle {
     pub fn is_valid(&self) -> bool {
         self.index.is_some()
     }
+
+    /// The index of the animation in the spritesheet, if the handle is valid
+    pub(crate) fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Whether the handle can be used on the specified spritesheet, which is
+    /// true if it came from the spritesheet or if it was created with
+    /// [`AnimHandle::from_index`]. Handles from a spritesheet can still be
+    /// used after it's rebuilt, such as when it's hot reloaded
+    pub fn is_for_sheet(&self, sheet: &Spritesheet) -> bool {
+        self.sheet == 0 || self.sheet == sheet.sheet_id
+    }
+
+    /// Whether the handles refer to the same animation, which is the case if
+    /// they have the same index and are for the same spritesheet, or if either
+    /// of them was created with [`AnimHandle::from_index`]. Unlike `==`, this
+    /// isn't transitive
+    pub fn matches(&self, other: &AnimHandle) -> bool {
+        self.index == other.index
+            && (self.sheet == 0 || other.sheet == 0 || self.sheet == other.sheet)
+    }
+
+    /// Get a handle to the animation on the spritesheet `to` that has the same
+    /// name as the animation this handle refers to on the spritesheet `from`,
+    /// the handle is invalid if either spritesheet doesn't have the animation
+    pub fn retarget(&self, from: &Spritesheet, to: &Spritesheet) -> AnimHandle {
+        match from.get_anim(self) {
+            Ok(anim) => to.get_anim_handle(&anim.name),
+            Err(_) => AnimHandle::invalid(),
+        }
+    }
+}
+
+// Utility: --------------------------------------------------------------------
+
+static NEXT_SHEET_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A new unique id for a spritesheet that isn't loaded from an asset path
+fn next_sheet_id() -> u64 {
+    NEXT_SHEET_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Declare an enum with a variant for each animation of a spritesheet, which
+/// implements [`AnimLabel`] so it can be used with
+/// [`Spritesheet::get_label_handle`] and verified with
+/// [`Spritesheet::verify_anim_labels`]
+///
+/// # Example
+/// ```
+/// # use bevy_asepritesheet::prelude::*;
+/// anim_labels! {
+///     pub enum WitchAnim {
+///         Idle = "idle",
+///         Run = "running",
+///         Hurt = "damage",
+///     }
+/// }
+/// assert_eq!(WitchAnim::Run.anim_name(), "running");
+/// ```
+#[macro_export]
+macro_rules! anim_labels {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $anim:literal),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant),*
+        }
+
+        impl $crate::sprite::AnimLabel for $name {
+            fn all() -> &'static [Self] {
+                &[$($name::$variant),*]
+            }
+
+            fn anim_name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $anim),*
+                }
+            }
+        }
+    };
+}
+
+/// The position of the anchor in the original untrimmed frame, in aseprite
+/// pixel coordinates where the origin is the top left and y points down
+fn anchor_source_position(anchor: &Anchor, source_size: aseprite_data::SizeData) -> Vec2 {
+    anchor
+        .as_vec()
+        // offset by 0.5 since bevy considers <-0.5,-0.5> to be top left
+        // for some reason
+        .add(Vec2::splat(0.5))
+        // get original frame size data
+        .mul(Vec2::from(source_size))
+}
+
+/// Convert a point in aseprite pixel coordinates into bevy coordinates
+/// relative to the specified anchor position
+fn source_point_to_local(point: Vec2, anchor_pos: Vec2) -> Vec2 {
+    Vec2::new(point.x - anchor_pos.x, anchor_pos.y - point.y)
+}
+
+/// Convert a rect in aseprite pixel coordinates into bevy coordinates relative
+/// to the specified anchor position
+fn source_rect_to_local(rect: Rect, anchor_pos: Vec2) -> Rect {
+    Rect::from_corners(
+        source_point_to_local(rect.min, anchor_pos),
+        source_point_to_local(rect.max, anchor_pos),
+    )
 }
 
 // Handle Verification: --------------------------------------------------------
@@ -358,15 +1095,56 @@ pub struct NeedsSheetAtlasHandle;
 #[derive(Component, Default, Clone, Copy, Reflect, Debug, Serialize, Deserialize)]
 pub struct NeedsSheetImageHandle;
 
+/// Marks the entities which use a spritesheet that was modified, such as when
+/// it was hot reloaded, or which were given a different spritesheet, so that
+/// their image and atlas handles and their slice colliders are updated
+#[allow(clippy::type_complexity)]
+pub(crate) fn mark_modified_sheet_entities(
+    mut commands: Commands,
+    mut sheet_events: EventReader<AssetEvent<Spritesheet>>,
+    mut query: Query<
+        (
+            Entity,
+            Ref<Handle<Spritesheet>>,
+            Option<&mut SliceColliders>,
+        ),
+        With<Sprite>,
+    >,
+) {
+    let modified: Vec<AssetId<Spritesheet>> = sheet_events
+        .read()
+        .filter_map(|evt| match evt {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (ent, sheet_handle, maybe_colliders) in &mut query {
+        let swapped = sheet_handle.is_changed() && !sheet_handle.is_added();
+        if swapped || modified.contains(&sheet_handle.id()) {
+            commands
+                .entity(ent)
+                .insert((NeedsSheetImageHandle, NeedsSheetAtlasHandle));
+            if let Some(mut colliders) = maybe_colliders {
+                colliders.mark_stale();
+            }
+        }
+    }
+}
+
 pub(crate) fn add_needed_img_handles(
     mut commands: Commands,
     mut query: Query<(Entity, &Handle<Spritesheet>), With<NeedsSheetImageHandle>>,
     sheet_assets: Res<Assets<Spritesheet>>,
 ) {
     for (ent, sheet_handle) in &mut query {
+        // wait until the spritesheet has been generated from its data, before
+        // then it only has a placeholder image
         let Some(sheet) = sheet_assets.get(sheet_handle) else {
             continue;
         };
+        if sheet.frames.is_empty() {
+            continue;
+        }
         let mut ent_cmd = commands.entity(ent);
         ent_cmd.insert(sheet.img_handle());
         ent_cmd.remove::<NeedsSheetImageHandle>();
@@ -382,15 +1160,22 @@ pub(crate) fn add_needed_atlas_handles(
     sheet_assets: Res<Assets<Spritesheet>>,
 ) {
     for (ent, sheet_handle, op_atlas) in &mut query {
-        let Some(sheet) = sheet_assets.get(sheet_handle) else {
+        // wait until the spritesheet's atlas has been created
+        let Some(atlas_handle) = sheet_assets
+            .get(sheet_handle)
+            .and_then(|sheet| sheet.atlas_handle())
+        else {
             continue;
         };
-        if let Some(atlas_handle) = sheet.atlas_handle() {
-            if let Some(mut atlas) = op_atlas {
-                atlas.layout = atlas_handle;
-            }
+        let mut ent_cmd = commands.entity(ent);
+        if let Some(mut atlas) = op_atlas {
+            atlas.layout = atlas_handle;
         } else {
-            commands.entity(ent).remove::<NeedsSheetAtlasHandle>();
+            ent_cmd.insert(TextureAtlas {
+                layout: atlas_handle,
+                index: 0,
+            });
         }
+        ent_cmd.remove::<NeedsSheetAtlasHandle>();
     }
 }
@@ -0,0 +1,458 @@
+use crate::{animator::SpriteAnimator, sprite::*};
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+// Struct Definitions: ---------------------------------------------------------
+
+/// A parameter value of an [`AnimStateMachine`] that transition conditions
+/// can be based on
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AnimParam {
+    Bool(bool),
+    Float(f32),
+    /// A flag that stays set until a transition that checks it is taken
+    Trigger(bool),
+}
+
+/// A condition that must be met for an [`AnimTransition`] to be taken
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AnimCondition {
+    /// The bool parameter with the specified name must have the specified
+    /// value
+    Bool(String, bool),
+    /// The float parameter with the specified name must be greater than the
+    /// specified value
+    FloatGreater(String, f32),
+    /// The float parameter with the specified name must be less than the
+    /// specified value
+    FloatLess(String, f32),
+    /// The trigger parameter with the specified name must be set, it's reset
+    /// when the transition is taken
+    Trigger(String),
+}
+
+/// A rule for switching from one animation to another in an
+/// [`AnimStateMachine`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimTransition {
+    /// The animation that must be playing for the transition to be taken, or
+    /// [`None`] if it can be taken from any animation
+    pub from: Option<AnimHandle>,
+    /// The animation that will be played when the transition is taken
+    pub to: AnimHandle,
+    /// The conditions that must all be met for the transition to be taken
+    pub conditions: Vec<AnimCondition>,
+    /// The normalized time that the current animation must have played for
+    /// before the transition can be taken, values above 1 wait for looping
+    /// animations to play multiple times. It's also reached when the
+    /// animation is stopped or replaced by its end action, see
+    /// [`SpriteAnimator::ended_anim`]
+    pub exit_time: Option<f32>,
+    /// Whether the transition waits until the current animation has played
+    /// all the way through, the same as an exit time of 1
+    pub wait_for_end: bool,
+}
+
+/// A component that sits beside a [`SpriteAnimator`] and switches between
+/// animations based on a set of parameters and transition rules. The
+/// transitions are evaluated in order every update, and only the first
+/// transition that can be taken is taken
+///
+/// The transitions refer to the animations of a specific spritesheet, so they
+/// stop matching once the entity's spritesheet is changed, such as with
+/// [`swap_spritesheet`](crate::prelude::swap_spritesheet). Use an
+/// [`AnimGraph`](crate::prelude::AnimGraph) instead, which refers to the
+/// animations by name and rebuilds the state machine for the new spritesheet
+#[derive(Component, Clone, Debug, Default)]
+pub struct AnimStateMachine {
+    params: HashMap<String, AnimParam>,
+    transitions: Vec<AnimTransition>,
+}
+
+// Struct Implementations: -----------------------------------------------------
+
+impl AnimCondition {
+    /// Whether or not the condition is met by the specified parameters
+    fn is_met(&self, params: &HashMap<String, AnimParam>) -> bool {
+        match self {
+            AnimCondition::Bool(name, value) => params.get(name) == Some(&AnimParam::Bool(*value)),
+            AnimCondition::FloatGreater(name, value) => {
+                matches!(params.get(name), Some(AnimParam::Float(param)) if param > value)
+            }
+            AnimCondition::FloatLess(name, value) => {
+                matches!(params.get(name), Some(AnimParam::Float(param)) if param < value)
+            }
+            AnimCondition::Trigger(name) => params.get(name) == Some(&AnimParam::Trigger(true)),
+        }
+    }
+}
+
+impl AnimTransition {
+    /// Create a transition from the specified animation to another, which is
+    /// taken as soon as possible unless conditions are added to it
+    pub fn new(from: AnimHandle, to: AnimHandle) -> Self {
+        Self {
+            from: Some(from),
+            to,
+            conditions: Vec::new(),
+            exit_time: None,
+            wait_for_end: false,
+        }
+    }
+
+    /// Create a transition that can be taken from any animation except for
+    /// the one it transitions to
+    pub fn from_any(to: AnimHandle) -> Self {
+        Self {
+            from: None,
+            ..Self::new(to, to)
+        }
+    }
+
+    /// Add a condition that must be met for the transition to be taken
+    pub fn when(mut self, condition: AnimCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Set the normalized time that the current animation must have played
+    /// for before the transition can be taken
+    pub fn with_exit_time(mut self, exit_time: f32) -> Self {
+        self.exit_time = Some(exit_time);
+        self
+    }
+
+    /// Wait until the current animation has played all the way through before
+    /// the transition can be taken
+    pub fn wait_for_end(mut self) -> Self {
+        self.wait_for_end = true;
+        self
+    }
+
+    /// Whether or not the transition can be taken from the animator's current
+    /// state with the specified parameters
+    fn can_take(
+        &self,
+        animator: &SpriteAnimator,
+        sheet: &Spritesheet,
+        params: &HashMap<String, AnimParam>,
+    ) -> bool {
+        let exit_time = match (self.exit_time, self.wait_for_end) {
+            (Some(exit_time), true) => Some(exit_time.max(1.0)),
+            (None, true) => Some(1.0),
+            (exit_time, false) => exit_time,
+        };
+
+        // an animation that was stopped or replaced by its end action has
+        // played all the way through, so transitions that wait for it can
+        // still be taken from it until the animation is changed
+        let ended = exit_time.is_some()
+            && animator.ended_anim().is_some_and(|ended| match self.from {
+                Some(from) => ended.matches(&from),
+                None => !ended.matches(&self.to),
+            });
+        if !ended {
+            match self.from {
+                Some(from) => {
+                    if !animator.is_cur_anim(from) {
+                        return false;
+                    }
+                }
+                None => {
+                    if animator.is_cur_anim(self.to) {
+                        return false;
+                    }
+                }
+            }
+            if let Some(exit_time) = exit_time {
+                if animator.get_cur_time_normalized(sheet) < exit_time {
+                    return false;
+                }
+            }
+        }
+        self.conditions
+            .iter()
+            .all(|condition| condition.is_met(params))
+    }
+}
+
+impl AnimStateMachine {
+    /// Create a state machine without any parameters or transitions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a transition to the state machine, transitions are checked in the
+    /// order they are added
+    pub fn with_transition(mut self, transition: AnimTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    /// Add a transition to the state machine, transitions are checked in the
+    /// order they are added
+    pub fn add_transition(&mut self, transition: AnimTransition) {
+        self.transitions.push(transition);
+    }
+
+    /// All the transitions in the state machine, in the order they're checked
+    pub fn transitions(&self) -> &[AnimTransition] {
+        &self.transitions
+    }
+
+    /// Remove all the transitions from the state machine
+    pub fn clear_transitions(&mut self) {
+        self.transitions.clear();
+    }
+
+    /// Get the value of the parameter with the specified name, if it exists
+    pub fn get_param<T: AsRef<str>>(&self, name: T) -> Option<AnimParam> {
+        self.params.get(name.as_ref()).copied()
+    }
+
+    /// Set the value of the parameter with the specified name
+    pub fn set_param(&mut self, name: impl Into<String>, value: AnimParam) {
+        self.params.insert(name.into(), value);
+    }
+
+    /// Set the value of the bool parameter with the specified name
+    pub fn set_bool(&mut self, name: impl Into<String>, value: bool) {
+        self.set_param(name, AnimParam::Bool(value));
+    }
+
+    /// Set the value of the float parameter with the specified name
+    pub fn set_float(&mut self, name: impl Into<String>, value: f32) {
+        self.set_param(name, AnimParam::Float(value));
+    }
+
+    /// Set the trigger parameter with the specified name, it stays set until
+    /// a transition that checks it is taken
+    pub fn set_trigger(&mut self, name: impl Into<String>) {
+        self.set_param(name, AnimParam::Trigger(true));
+    }
+
+    /// Reset the trigger parameter with the specified name
+    pub fn reset_trigger(&mut self, name: impl Into<String>) {
+        self.set_param(name, AnimParam::Trigger(false));
+    }
+
+    /// Get the value of the bool parameter with the specified name, false if
+    /// it doesn't exist or isn't a bool
+    pub fn get_bool<T: AsRef<str>>(&self, name: T) -> bool {
+        matches!(self.get_param(name), Some(AnimParam::Bool(true)))
+    }
+
+    /// Get the value of the float parameter with the specified name, 0 if it
+    /// doesn't exist or isn't a float
+    pub fn get_float<T: AsRef<str>>(&self, name: T) -> f32 {
+        match self.get_param(name) {
+            Some(AnimParam::Float(value)) => value,
+            _ => 0.0,
+        }
+    }
+
+    /// Find the first transition that can be taken from the animator's
+    /// current state, reset the triggers it checks, and return the animation
+    /// it transitions to
+    pub fn evaluate(
+        &mut self,
+        animator: &SpriteAnimator,
+        sheet: &Spritesheet,
+    ) -> Option<AnimHandle> {
+        let transition = self
+            .transitions
+            .iter()
+            .find(|transition| transition.can_take(animator, sheet, &self.params))?;
+        for condition in &transition.conditions {
+            if let AnimCondition::Trigger(name) = condition {
+                self.params.insert(name.clone(), AnimParam::Trigger(false));
+            }
+        }
+        Some(transition.to)
+    }
+}
+
+// Systems: --------------------------------------------------------------------
+
+/// system that evaluates the transitions of every [`AnimStateMachine`] and
+/// sets the animation on its [`SpriteAnimator`] when one is taken. It runs in
+/// the plugin schedule before the sprites are animated
+pub fn update_anim_state_machines(
+    spritesheet_assets: Res<Assets<Spritesheet>>,
+    mut query: Query<(
+        &mut AnimStateMachine,
+        &mut SpriteAnimator,
+        &Handle<Spritesheet>,
+    )>,
+) {
+    for (mut state_machine, mut animator, sheet_handle) in &mut query {
+        let Some(sheet) = spritesheet_assets.get(sheet_handle) else {
+            continue;
+        };
+        if let Some(anim) = state_machine.evaluate(&animator, sheet) {
+            animator.set_anim(anim);
+        }
+    }
+}
+
+// Tests: ----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animator::tests::{step, test_sheet};
+
+    #[test]
+    fn wait_for_end_of_stopped_anim() {
+        let mut sheet = test_sheet(&[("attack", 0, 3, "forward"), ("idle", 4, 5, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let idle = sheet.get_anim_handle("idle");
+        sheet.get_anim_mut(&attack).unwrap().end_action = AnimEndAction::Stop;
+        let mut state_machine = AnimStateMachine::new()
+            .with_transition(AnimTransition::new(attack, idle).wait_for_end());
+        let mut animator = SpriteAnimator::from_anim(attack);
+
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+
+        // the attack animation is stopped when it ends
+        step(&mut animator, &sheet, 0.4);
+        assert!(animator.cur_anim().is_none());
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(idle));
+    }
+
+    #[test]
+    fn wait_for_end_with_late_condition() {
+        let mut sheet = test_sheet(&[("attack", 0, 3, "forward"), ("idle", 4, 5, "forward")]);
+        let attack = sheet.get_anim_handle("attack");
+        let idle = sheet.get_anim_handle("idle");
+        sheet.get_anim_mut(&attack).unwrap().end_action = AnimEndAction::Stop;
+        let mut state_machine = AnimStateMachine::new().with_transition(
+            AnimTransition::new(attack, idle)
+                .wait_for_end()
+                .when(AnimCondition::Bool("grounded".to_string(), true)),
+        );
+        let mut animator = SpriteAnimator::from_anim(attack);
+        step(&mut animator, &sheet, 0.05);
+        step(&mut animator, &sheet, 0.4);
+
+        // the transition can still be taken a few updates after the end
+        for _ in 0..3 {
+            step(&mut animator, &sheet, 0.1);
+            assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        }
+        state_machine.set_bool("grounded", true);
+        step(&mut animator, &sheet, 0.1);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(idle));
+    }
+
+    #[test]
+    fn bool_and_float_conditions() {
+        let sheet = test_sheet(&[("walk", 0, 3, "forward"), ("run", 4, 5, "forward")]);
+        let walk = sheet.get_anim_handle("walk");
+        let run = sheet.get_anim_handle("run");
+        let mut state_machine = AnimStateMachine::new()
+            .with_transition(
+                AnimTransition::new(walk, run)
+                    .when(AnimCondition::Bool("moving".to_string(), true))
+                    .when(AnimCondition::FloatGreater("speed".to_string(), 2.0)),
+            )
+            .with_transition(
+                AnimTransition::new(run, walk)
+                    .when(AnimCondition::FloatLess("speed".to_string(), 1.0)),
+            );
+        let mut animator = SpriteAnimator::from_anim(walk);
+        step(&mut animator, &sheet, 0.05);
+
+        // missing parameters and parameters of another type don't match
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        state_machine.set_bool("moving", true);
+        state_machine.set_param("speed", AnimParam::Bool(true));
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+
+        // every condition has to be met, and floats are compared strictly
+        state_machine.set_float("speed", 2.0);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        state_machine.set_float("speed", 3.0);
+        state_machine.set_bool("moving", false);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        state_machine.set_bool("moving", true);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(run));
+
+        // only the transitions from the current animation are taken
+        animator.set_anim(run);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        state_machine.set_float("speed", 0.5);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(walk));
+    }
+
+    #[test]
+    fn trigger_resets_when_taken() {
+        let sheet = test_sheet(&[("idle", 0, 3, "forward"), ("hurt", 4, 5, "forward")]);
+        let idle = sheet.get_anim_handle("idle");
+        let hurt = sheet.get_anim_handle("hurt");
+        let mut state_machine = AnimStateMachine::new().with_transition(
+            AnimTransition::from_any(hurt)
+                .when(AnimCondition::Trigger("hit".to_string()))
+                .when(AnimCondition::Bool("alive".to_string(), true)),
+        );
+        let mut animator = SpriteAnimator::from_anim(idle);
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+
+        // the trigger stays set until the transition is taken
+        state_machine.set_trigger("hit");
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+        assert_eq!(
+            state_machine.get_param("hit"),
+            Some(AnimParam::Trigger(true))
+        );
+        state_machine.set_bool("alive", true);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(hurt));
+        assert_eq!(
+            state_machine.get_param("hit"),
+            Some(AnimParam::Trigger(false))
+        );
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+    }
+
+    #[test]
+    fn exit_time_waits_for_normalized_time() {
+        let sheet = test_sheet(&[("walk", 0, 3, "forward"), ("idle", 4, 5, "forward")]);
+        let walk = sheet.get_anim_handle("walk");
+        let idle = sheet.get_anim_handle("idle");
+        let mut halfway = AnimStateMachine::new()
+            .with_transition(AnimTransition::new(walk, idle).with_exit_time(0.5));
+        let mut second_loop = AnimStateMachine::new()
+            .with_transition(AnimTransition::new(walk, idle).with_exit_time(1.5));
+        let mut animator = SpriteAnimator::from_anim(walk);
+
+        step(&mut animator, &sheet, 0.15);
+        assert_eq!(halfway.evaluate(&animator, &sheet), None);
+        step(&mut animator, &sheet, 0.1);
+        assert_eq!(halfway.evaluate(&animator, &sheet), Some(idle));
+
+        // exit times past 1 wait for the looping animation to play again
+        step(&mut animator, &sheet, 0.25);
+        assert_eq!(second_loop.evaluate(&animator, &sheet), None);
+        step(&mut animator, &sheet, 0.15);
+        assert_eq!(second_loop.evaluate(&animator, &sheet), Some(idle));
+    }
+
+    #[test]
+    fn from_any_skips_its_own_target() {
+        let sheet = test_sheet(&[("idle", 0, 3, "forward"), ("hurt", 4, 5, "forward")]);
+        let idle = sheet.get_anim_handle("idle");
+        let hurt = sheet.get_anim_handle("hurt");
+        let mut state_machine =
+            AnimStateMachine::new().with_transition(AnimTransition::from_any(hurt));
+        let mut animator = SpriteAnimator::from_anim(idle);
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), Some(hurt));
+
+        // the transition doesn't keep restarting the animation it plays
+        animator.set_anim(hurt);
+        step(&mut animator, &sheet, 0.05);
+        assert_eq!(state_machine.evaluate(&animator, &sheet), None);
+    }
+}